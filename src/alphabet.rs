@@ -0,0 +1,286 @@
+use std::{fmt, str::FromStr};
+
+const DECIMAL: &[u8] = b"0123456789";
+const HEX: &[u8] = b"0123456789abcdef";
+const BASE36: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE62: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const IDENTIFIER: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$";
+
+/// Symbols the varying suffix of a candidate is written in.
+///
+/// Index `n` is written as an ordinary base-`radix` numeral with the first symbol as zero, so
+/// index 0 is the empty suffix and the first symbol never leads. With [`Alphabet::Decimal`] this
+/// is the plain `name123` numbering.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Alphabet {
+    /// `0-9`
+    #[default]
+    Decimal,
+    /// `0-9a-f`
+    Hex,
+    /// `0-9a-z`
+    Base36,
+    /// `0-9a-zA-Z`
+    Base62,
+    /// Every character allowed in a Solidity identifier, `0-9a-zA-Z_$`
+    Identifier,
+    /// User supplied identifier characters, the first one acts as zero
+    Custom(Vec<u8>),
+}
+
+/// Reasons an alphabet can not be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlphabetError {
+    /// Not one of the named alphabets nor `custom:SYMBOLS`
+    Unknown(String),
+    /// A numbering needs at least two symbols
+    TooFewSymbols(usize),
+    /// Symbol listed more than once, the numbering would not be unique
+    DuplicateSymbol(char),
+    /// Symbol that can not appear in a Solidity identifier
+    InvalidSymbol(char),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphabetError::Unknown(name) => write!(f, "unknown alphabet {:?}", name),
+            AlphabetError::TooFewSymbols(count) => {
+                write!(f, "alphabet needs at least 2 symbols, got {}", count)
+            }
+            AlphabetError::DuplicateSymbol(c) => write!(f, "symbol {:?} is listed twice", c),
+            AlphabetError::InvalidSymbol(c) => {
+                write!(f, "symbol {:?} is not allowed in an identifier", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AlphabetError {}
+
+impl Alphabet {
+    /// Custom alphabet from identifier characters, in numbering order
+    pub fn custom(symbols: &str) -> Result<Self, AlphabetError> {
+        let mut seen = [false; 128];
+        for c in symbols.chars() {
+            if !(c.is_ascii_alphanumeric() || c == '_' || c == '$') {
+                return Err(AlphabetError::InvalidSymbol(c));
+            }
+            if std::mem::replace(&mut seen[c as usize], true) {
+                return Err(AlphabetError::DuplicateSymbol(c));
+            }
+        }
+        if symbols.len() < 2 {
+            return Err(AlphabetError::TooFewSymbols(symbols.len()));
+        }
+        Ok(Alphabet::Custom(symbols.as_bytes().to_vec()))
+    }
+
+    pub fn symbols(&self) -> &[u8] {
+        match self {
+            Alphabet::Decimal => DECIMAL,
+            Alphabet::Hex => HEX,
+            Alphabet::Base36 => BASE36,
+            Alphabet::Base62 => BASE62,
+            Alphabet::Identifier => IDENTIFIER,
+            Alphabet::Custom(symbols) => symbols,
+        }
+    }
+
+    pub fn radix(&self) -> u64 {
+        self.symbols().len() as u64
+    }
+
+    /// Writes the numeral for `index` to the end of `digits` and returns it, empty for index 0
+    #[inline]
+    pub fn digits<'a>(&self, index: u64, digits: &'a mut [u8; 64]) -> &'a [u8] {
+        let symbols = self.symbols();
+        let radix = symbols.len() as u64;
+        // 64 is enough for a u64 in base 2
+        let mut pos = digits.len();
+        let mut n = index;
+
+        while n > 0 {
+            pos -= 1;
+            digits[pos] = symbols[(n % radix) as usize];
+            n /= radix;
+        }
+
+        &digits[pos..]
+    }
+
+    /// Like [`Alphabet::digits`] but left padded with the zero symbol to `width` symbols, which
+    /// must be enough for `index`
+    #[inline]
+    pub fn padded_digits<'a>(
+        &self,
+        index: u64,
+        width: usize,
+        digits: &'a mut [u8; 64],
+    ) -> &'a [u8] {
+        let len = self.digits(index, digits).len();
+        debug_assert!(len <= width, "{} does not fit in {} symbols", index, width);
+        let end = digits.len();
+        digits[end - width..end - len].fill(self.symbols()[0]);
+        &digits[end - width..]
+    }
+
+    /// Counter that steps numerals of this alphabet in place
+    pub fn odometer(&self) -> Odometer {
+        let symbols = self.symbols();
+        let mut successors = [0u8; 256];
+        for pair in symbols.windows(2) {
+            successors[pair[0] as usize] = pair[1];
+        }
+        Odometer { successors, zero: symbols[0], one: symbols[1] }
+    }
+
+    /// Number of numerals with exactly `width` symbols when padding is allowed, `None` if more
+    /// than a `u64` can count
+    pub fn numerals(&self, width: u32) -> Option<u64> {
+        self.radix().checked_pow(width)
+    }
+
+    /// Appends the suffix for `index` to `out`, nothing for index 0
+    #[inline]
+    pub fn encode(&self, index: u64, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.digits(index, &mut [0u8; 64]));
+    }
+
+    /// Suffix for `index` as a string
+    pub fn render(&self, index: u64) -> String {
+        let mut out = Vec::new();
+        self.encode(index, &mut out);
+        // Every alphabet is ASCII
+        String::from_utf8(out).unwrap()
+    }
+}
+
+/// Adds one to a numeral written with [`Alphabet::digits`] without going through its index, only
+/// the digits that change are touched, like the wheels of an odometer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Odometer {
+    /// Symbol after each symbol in numbering order, 0 after the last one
+    successors: [u8; 256],
+    zero: u8,
+    one: u8,
+}
+
+impl Odometer {
+    /// Steps `digits` to the next numeral of the same length and returns `false`, or sets them
+    /// all to the zero symbol and returns `true` when the most significant digit carries
+    #[inline]
+    pub fn increment(&self, digits: &mut [u8]) -> bool {
+        for digit in digits.iter_mut().rev() {
+            let next = self.successors[*digit as usize];
+            if next != 0 {
+                *digit = next;
+                return false;
+            }
+            *digit = self.zero;
+        }
+        true
+    }
+
+    /// Symbol a numeral grows by on the left after a carry, `9` + 1 is `10`
+    pub fn one(&self) -> u8 {
+        self.one
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = AlphabetError;
+
+    /// Parses `decimal`, `hex`, `base36`, `base62`, `identifier` or `custom:SYMBOLS`
+    fn from_str(alphabet: &str) -> Result<Self, Self::Err> {
+        match alphabet {
+            "decimal" => Ok(Alphabet::Decimal),
+            "hex" => Ok(Alphabet::Hex),
+            "base36" => Ok(Alphabet::Base36),
+            "base62" => Ok(Alphabet::Base62),
+            "identifier" => Ok(Alphabet::Identifier),
+            _ => match alphabet.strip_prefix("custom:") {
+                Some(symbols) => Alphabet::custom(symbols),
+                None => Err(AlphabetError::Unknown(alphabet.to_string())),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Alphabet, AlphabetError};
+
+    #[test]
+    fn test_render() {
+        assert_eq!(Alphabet::Decimal.render(0), "");
+        assert_eq!(Alphabet::Decimal.render(533813959), "533813959");
+        assert_eq!(Alphabet::Decimal.render(u64::MAX), u64::MAX.to_string());
+        assert_eq!(Alphabet::Hex.render(0xbeef), "beef");
+        assert_eq!(Alphabet::Base36.render(35), "z");
+        assert_eq!(Alphabet::Base62.render(62), "10");
+        assert_eq!(Alphabet::Identifier.render(63), "$");
+        assert_eq!(Alphabet::Identifier.render(64 * 64 - 1), "$$");
+        assert_eq!(Alphabet::custom("ab").unwrap().render(6), "bba");
+    }
+
+    #[test]
+    fn test_padded() {
+        let mut digits = [0u8; 64];
+        assert_eq!(Alphabet::Decimal.padded_digits(417, 6, &mut digits), b"000417");
+        assert_eq!(Alphabet::Decimal.padded_digits(0, 3, &mut digits), b"000");
+        assert_eq!(Alphabet::Hex.padded_digits(0xff, 2, &mut digits), b"ff");
+        assert_eq!(Alphabet::Base36.numerals(2), Some(36 * 36));
+        assert_eq!(Alphabet::Identifier.numerals(11), None);
+    }
+
+    #[test]
+    fn test_shorter_than_decimal() {
+        // The decimal README example needs 9 characters, the identifier alphabet 5
+        assert_eq!(Alphabet::Identifier.render(533813959).len(), 5);
+        assert_eq!(Alphabet::Identifier.render(u64::MAX).len(), 11);
+    }
+
+    #[test]
+    fn test_unique() {
+        let alphabet = Alphabet::Base62;
+        let mut suffixes: Vec<String> = (0..100_000).map(|i| alphabet.render(i)).collect();
+        suffixes.sort();
+        suffixes.dedup();
+        assert_eq!(suffixes.len(), 100_000);
+    }
+
+    #[test]
+    fn test_odometer() {
+        for alphabet in [Alphabet::Decimal, Alphabet::Identifier, Alphabet::custom("ab").unwrap()] {
+            let odometer = alphabet.odometer();
+            let mut numeral = Vec::new();
+            for index in 1..10_000 {
+                if odometer.increment(&mut numeral) {
+                    numeral.insert(0, odometer.one());
+                }
+                assert_eq!(numeral, alphabet.render(index).as_bytes());
+            }
+        }
+
+        let odometer = Alphabet::Hex.odometer();
+        let mut digits = *b"0ff";
+        assert!(!odometer.increment(&mut digits));
+        assert_eq!(&digits, b"100");
+        let mut digits = *b"ff";
+        assert!(odometer.increment(&mut digits));
+        assert_eq!(&digits, b"00");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("hex".parse(), Ok(Alphabet::Hex));
+        assert_eq!("identifier".parse(), Ok(Alphabet::Identifier));
+        assert_eq!("custom:xyz_".parse(), Ok(Alphabet::Custom(b"xyz_".to_vec())));
+        assert_eq!("octal".parse::<Alphabet>(), Err(AlphabetError::Unknown("octal".to_string())));
+        assert_eq!(Alphabet::custom("a"), Err(AlphabetError::TooFewSymbols(1)));
+        assert_eq!(Alphabet::custom("abca"), Err(AlphabetError::DuplicateSymbol('a')));
+        assert_eq!(Alphabet::custom("ab-"), Err(AlphabetError::InvalidSymbol('-')));
+        assert_eq!(Alphabet::custom("aé"), Err(AlphabetError::InvalidSymbol('é')));
+    }
+}
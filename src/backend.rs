@@ -0,0 +1,313 @@
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "portable")]
+use crate::keccak::{Kernel, SelectorHasher};
+use crate::{candidate::CandidateGenerator, keccak::MAX_LANES};
+
+#[cfg(not(any(feature = "asm", feature = "portable")))]
+compile_error!("enable at least one Keccak-256 backend, the `asm` or `portable` feature");
+
+/// Implementations of Keccak-256, each behind the cargo feature of the same name.
+///
+/// A search hashes its candidates with the [`Hasher`] of the backend it is given and verifies
+/// matches with the same backend's [`Backend::keccak_256`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// `keccak-asm`, hand written assembly for common targets
+    #[cfg(feature = "asm")]
+    Asm,
+    /// The crate's own permutation in plain Rust, builds on every target. Searches run it on the
+    /// widest SIMD [`Kernel`] the CPU has.
+    #[cfg(feature = "portable")]
+    Portable,
+}
+
+/// Hashes the candidates of a search to their selectors, in batches of inputs that all start with
+/// the same fixed bytes
+pub trait Hasher: Send {
+    /// Inputs [`Hasher::selectors`] takes at once, at most [`MAX_LANES`]
+    fn lanes(&self) -> usize;
+
+    /// Selectors of up to [`Hasher::lanes`] inputs, the rest of the array is unspecified
+    fn selectors(&mut self, inputs: &[Vec<u8>]) -> [u32; MAX_LANES];
+}
+
+#[cfg(feature = "portable")]
+impl Hasher for SelectorHasher {
+    fn lanes(&self) -> usize {
+        SelectorHasher::lanes(self)
+    }
+
+    #[inline]
+    fn selectors(&mut self, inputs: &[Vec<u8>]) -> [u32; MAX_LANES] {
+        SelectorHasher::selectors(self, inputs)
+    }
+}
+
+/// `keccak-asm` with the fixed bytes absorbed once, each input continues from a copy of that
+#[cfg(feature = "asm")]
+struct AsmHasher {
+    fixed: usize,
+    /// `None` until the first input
+    prefix: Option<keccak_asm::Keccak256>,
+}
+
+#[cfg(feature = "asm")]
+impl Hasher for AsmHasher {
+    fn lanes(&self) -> usize {
+        MAX_LANES
+    }
+
+    #[inline]
+    fn selectors(&mut self, inputs: &[Vec<u8>]) -> [u32; MAX_LANES] {
+        use keccak_asm::Digest;
+
+        let fixed = self.fixed;
+        let mut selectors = [0u32; MAX_LANES];
+        for (selector, input) in selectors.iter_mut().zip(inputs) {
+            let prefix = self
+                .prefix
+                .get_or_insert_with(|| keccak_asm::Keccak256::new().chain_update(&input[..fixed]));
+            let hash = prefix.clone().chain_update(&input[fixed..]).finalize();
+            *selector = crate::hash_selector(&hash.into());
+        }
+        selectors
+    }
+}
+
+// Without a backend only the `compile_error!` above should be reported
+#[cfg_attr(not(any(feature = "asm", feature = "portable")), allow(unused_variables))]
+impl Backend {
+    /// Backends compiled in
+    pub const ALL: &[Backend] = &[
+        #[cfg(feature = "asm")]
+        Backend::Asm,
+        #[cfg(feature = "portable")]
+        Backend::Portable,
+    ];
+
+    #[inline]
+    pub fn keccak_256(self, input: &[u8]) -> [u8; 32] {
+        match self {
+            #[cfg(feature = "asm")]
+            Backend::Asm => keccak_asm::Keccak256::digest(input).into(),
+            #[cfg(feature = "portable")]
+            Backend::Portable => crate::keccak::keccak_256(input),
+        }
+    }
+
+    /// Hasher for a search whose inputs all start with the same `fixed` bytes
+    pub fn hasher(self, fixed: usize) -> Box<dyn Hasher> {
+        match self {
+            #[cfg(feature = "asm")]
+            Backend::Asm => Box::new(AsmHasher { fixed, prefix: None }),
+            #[cfg(feature = "portable")]
+            Backend::Portable => Box::new(SelectorHasher::new(fixed)),
+        }
+    }
+}
+
+impl Default for Backend {
+    /// The portable backend if a SIMD kernel runs it, where it searches fastest, otherwise the
+    /// first one compiled in
+    fn default() -> Self {
+        #[cfg(feature = "portable")]
+        if Kernel::detect() != Kernel::Scalar {
+            return Backend::Portable;
+        }
+        Backend::ALL[0]
+    }
+}
+
+#[cfg_attr(not(any(feature = "asm", feature = "portable")), allow(unused_variables))]
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "asm")]
+            Backend::Asm => write!(f, "asm"),
+            #[cfg(feature = "portable")]
+            Backend::Portable => write!(f, "portable"),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    /// Parses `asm` or `portable`, if compiled in
+    fn from_str(backend: &str) -> Result<Self, Self::Err> {
+        Backend::ALL.iter().copied().find(|b| b.to_string() == backend).ok_or_else(|| match backend
+        {
+            "asm" | "portable" => format!("backend {:?} is not compiled in", backend),
+            _ => format!("invalid backend {:?}", backend),
+        })
+    }
+}
+
+/// Selectors everything has to agree with, including lengths on either side of a block
+const KNOWN: &[(&[u8], u32)] = &[
+    (b"", 0xc5d24601),
+    (b"transfer(address,uint256)", 0xa9059cbb),
+    (b"balanceOf(address)", 0x70a08231),
+    (&[b'a'; 135], 0x34367dc2),
+    (&[b'a'; 136], 0xa6c4d403),
+    (&[b'a'; 137], 0xd869f639),
+];
+
+/// Signature two Keccak-256 implementations hash differently
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub signature: String,
+    /// Implementations and what they give, a digest or a selector in hex
+    pub left: (String, String),
+    pub right: (String, String),
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} gives {} but {} gives {} for {:?}",
+            self.left.0, self.left.1, self.right.0, self.right.1, self.signature
+        )
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold("0x".to_string(), |hex, byte| format!("{}{:02x}", hex, byte))
+}
+
+/// `Ok` if both implementations give the same for `signature`
+fn agree(
+    signature: &[u8],
+    left: (String, String),
+    right: (String, String),
+) -> Result<(), Mismatch> {
+    if left.1 == right.1 {
+        return Ok(());
+    }
+    Err(Mismatch { signature: String::from_utf8_lossy(signature).into_owned(), left, right })
+}
+
+/// Checks the [`Hasher`] a search on `backend` runs against every other backend compiled in, and
+/// returns how many signatures they agree on.
+///
+/// The corpus is a few known selectors, which every backend is also checked against, and the
+/// first `count` of `candidates`, which hash with the same fixed prefix and lengths as the search
+/// itself. With a single backend compiled in there is nothing independent to compare to and only
+/// the known selectors are checked.
+pub fn differential_check<G: CandidateGenerator + ?Sized>(
+    candidates: &G,
+    count: u64,
+    backend: Backend,
+) -> Result<usize, Mismatch> {
+    let selector = |hash: [u8; 32]| hex(&hash[..4]);
+    let search = format!("{} search", backend);
+    let others: Vec<Backend> = Backend::ALL.iter().copied().filter(|&b| b != backend).collect();
+
+    let mut hasher = backend.hasher(0);
+    for &(signature, expected) in KNOWN {
+        let expected = ("expected".to_string(), hex(&expected.to_be_bytes()));
+        let selectors = hasher.selectors(&[signature.to_vec()]);
+        agree(signature, expected.clone(), (search.clone(), hex(&selectors[0].to_be_bytes())))?;
+        for other in &others {
+            let hash = selector(other.keccak_256(signature));
+            agree(signature, expected.clone(), (other.to_string(), hash))?;
+        }
+    }
+    if others.is_empty() {
+        return Ok(KNOWN.len());
+    }
+
+    let signatures: Vec<Vec<u8>> = (0..candidates.range_end(Some(count)))
+        .map(|index| {
+            let mut buffer = Vec::new();
+            candidates.write(index, &mut buffer);
+            buffer
+        })
+        .collect();
+    let mut hasher = backend.hasher(candidates.fixed_prefix());
+    for batch in signatures.chunks(hasher.lanes()) {
+        let selectors = hasher.selectors(batch);
+        for (signature, searched) in batch.iter().zip(selectors) {
+            let searched = (search.clone(), hex(&searched.to_be_bytes()));
+            for other in &others {
+                let hash = selector(other.keccak_256(signature));
+                agree(signature, searched.clone(), (other.to_string(), hash))?;
+            }
+        }
+    }
+    Ok(KNOWN.len() + signatures.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backend, KNOWN, differential_check};
+    use crate::{candidate::Candidates, hash_selector};
+
+    #[test]
+    fn test_backends() {
+        let input: Vec<u8> = (0..500u32).map(|i| (i * 13 + 5) as u8).collect();
+        for backend in Backend::ALL {
+            for &(signature, selector) in KNOWN {
+                assert_eq!(hash_selector(&backend.keccak_256(signature)), selector, "{}", backend);
+            }
+            // Every length up to several blocks
+            for len in 0..input.len() {
+                let digest = backend.keccak_256(&input[..len]);
+                assert_eq!(
+                    digest,
+                    Backend::ALL[0].keccak_256(&input[..len]),
+                    "{} {}",
+                    backend,
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_backend() {
+        #[cfg(feature = "asm")]
+        assert_eq!("asm".parse(), Ok(Backend::Asm));
+        #[cfg(feature = "portable")]
+        assert_eq!("portable".parse(), Ok(Backend::Portable));
+        assert!("openssl".parse::<Backend>().is_err());
+        for backend in Backend::ALL {
+            assert_eq!(backend.to_string().parse(), Ok(*backend));
+        }
+    }
+
+    #[test]
+    fn test_differential_check() {
+        // Only the known selectors without a second backend to compare to
+        let compared =
+            |count: usize| if Backend::ALL.len() > 1 { KNOWN.len() + count } else { KNOWN.len() };
+        for &backend in Backend::ALL {
+            let candidates = Candidates::decimal("transfer", "address,uint256").unwrap();
+            assert_eq!(differential_check(&candidates, 1000, backend), Ok(compared(1000)));
+
+            // Every signature of more than a block, with the prefix absorbed once
+            let candidates = Candidates::decimal(&"swap".repeat(40), "uint256").unwrap();
+            assert_eq!(differential_check(&candidates, 100, backend), Ok(compared(100)));
+        }
+    }
+
+    #[test]
+    fn test_hashers() {
+        let prefix = "swapExactTokensForTokens".repeat(8);
+        let inputs: Vec<Vec<u8>> =
+            (0..100).map(|i| format!("{}{}(uint256)", prefix, i).into_bytes()).collect();
+        for &backend in Backend::ALL {
+            let mut hasher = backend.hasher(prefix.len());
+            for batch in inputs.chunks(hasher.lanes()) {
+                let selectors = hasher.selectors(batch);
+                for (input, selector) in batch.iter().zip(selectors) {
+                    assert_eq!(selector, hash_selector(&backend.keccak_256(input)), "{}", backend);
+                }
+            }
+        }
+    }
+}
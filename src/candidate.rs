@@ -0,0 +1,657 @@
+use std::ops::Range;
+
+use crate::{
+    alphabet::{Alphabet, Odometer},
+    identifier::{
+        NameError, is_identifier_char, validate_identifier, validate_parameters, validate_signature,
+    },
+    wordlist::Wordlists,
+};
+
+/// Placeholder in a name template that the varying part is written into
+pub const PLACEHOLDER: &str = "{}";
+
+/// Most symbols a numeral is written with, enough for a `u64` in base 2
+pub const MAX_WIDTH: u32 = 64;
+
+/// What the index of a candidate is written as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Suffix {
+    /// Numeral in the alphabet, left padded with its zero symbol to `width` if given
+    Numeral { alphabet: Alphabet, width: Option<u32> },
+    /// One word from each list, camelCased onto the name
+    Words(Wordlists),
+    /// Nothing, only the base names themselves
+    None,
+}
+
+impl Suffix {
+    /// Number of suffixes, `None` if there are more than a `u64` index can reach
+    pub fn space(&self) -> Option<u64> {
+        match self {
+            Suffix::Numeral { alphabet, width } => width.and_then(|width| alphabet.numerals(width)),
+            Suffix::Words(wordlists) => wordlists.combinations(),
+            Suffix::None => Some(1),
+        }
+    }
+
+    /// Suffix for `index` on its own, e.g. `417` or `getOwner`, before it is dealt out to the
+    /// placeholders of a template
+    pub fn render(&self, index: u64) -> String {
+        match self {
+            Suffix::Numeral { alphabet, width: Some(width) } => {
+                let digits =
+                    alphabet.padded_digits(index, *width as usize, &mut [0u8; 64]).to_vec();
+                // Every alphabet is ASCII
+                String::from_utf8(digits).unwrap()
+            }
+            Suffix::Numeral { alphabet, width: None } => alphabet.render(index),
+            Suffix::Words(wordlists) => {
+                let mut buffer = Vec::new();
+                (0..wordlists.len())
+                    .for_each(|list| wordlists.write_word(index, list, &mut buffer));
+                String::from_utf8(buffer).unwrap()
+            }
+            Suffix::None => String::new(),
+        }
+    }
+
+    /// Suffix indices grouped by the number of symbols they are written with, shortest first.
+    ///
+    /// `None` for words, whose lengths do not follow the index.
+    pub fn lengths(&self) -> Option<Vec<(usize, Range<u64>)>> {
+        match self {
+            Suffix::Numeral { alphabet, width: Some(width) } => {
+                let end = alphabet.numerals(*width).unwrap_or(u64::MAX);
+                Some(vec![(*width as usize, 0..end)])
+            }
+            Suffix::Numeral { alphabet, width: None } => {
+                // Index 0 is the empty suffix, then `radix^(n-1)..radix^n` have `n` symbols
+                let mut lengths = vec![(0, 0..1)];
+                let mut start = Some(1u64);
+                while let Some(first) = start {
+                    start = first.checked_mul(alphabet.radix());
+                    lengths.push((lengths.len(), first..start.unwrap_or(u64::MAX)));
+                }
+                Some(lengths)
+            }
+            Suffix::Words(_) => None,
+            Suffix::None => Some(vec![(0, 0..1)]),
+        }
+    }
+}
+
+/// Turns search indices into candidate signatures.
+///
+/// Searches are generic over this, so a custom naming strategy only has to map every index below
+/// [`CandidateGenerator::space`] to its bytes. [`Candidates`] is the built-in implementation,
+/// a name template with a numeral, words or nothing filled in, `name0`, `name1`, ... by default.
+pub trait CandidateGenerator: Sync {
+    /// Number of candidates, `None` if there are more than a `u64` index can reach
+    fn space(&self) -> Option<u64>;
+
+    /// Replaces the contents of `buffer` with the signature for `index`, which must be below
+    /// [`CandidateGenerator::space`]. Called once per hash, so it should not allocate.
+    fn write(&self, index: u64, buffer: &mut Vec<u8>);
+
+    /// Turns the signature for `index - 1` in `buffer` into the one for `index`. Searches walk
+    /// their chunk of indices in order with one buffer, so only the bytes that change need to be
+    /// touched. Writes the signature from scratch by default.
+    #[inline]
+    fn advance(&self, index: u64, buffer: &mut Vec<u8>) {
+        self.write(index, buffer);
+    }
+
+    /// Number of leading bytes every signature shares, which hashing only loads once
+    fn fixed_prefix(&self) -> usize {
+        0
+    }
+
+    /// Signature for `index` as shown to the user
+    fn render(&self, index: u64) -> String {
+        let mut buffer = Vec::new();
+        self.write(index, &mut buffer);
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+
+    /// Clamps the end of a search range to the number of candidates
+    fn range_end(&self, end: Option<u64>) -> u64 {
+        let end = end.unwrap_or(u64::MAX);
+        self.space().map_or(end, |space| end.min(space))
+    }
+
+    /// Checks a matching signature before it is reported, by default that it names a valid,
+    /// non-reserved identifier. Only runs on hits, so it may be slow.
+    fn check(&self, signature: &[u8]) -> Result<(), NameError> {
+        validate_signature(signature)
+    }
+}
+
+/// The function signatures a search walks through, a name template with the varying part for
+/// each index filled in, followed by `(parameters)`.
+///
+/// Without a [`PLACEHOLDER`] the varying part goes at the end of the name, `transfer` is the
+/// same as `transfer{}`. With several placeholders the digits of the numeral are dealt out in
+/// turn, least significant first, so `v{}_transfer{}` walks `v_transfer`, `v1_transfer`, ...,
+/// `v9_transfer`, `v0_transfer1`, ... and every placeholder keeps growing as the index does.
+/// Words are dealt out the same way in list order.
+///
+/// With a fixed width every numeral is left padded with the zero symbol of the alphabet, e.g.
+/// `transfer_000417`, so all candidates have the same length and there are only
+/// [`Candidates::space`] of them. Wordlists are bounded by their number of combinations.
+///
+/// Several bases, e.g. style variants of a name, take turns: index `n` is suffix `n / bases` on
+/// base `n % bases`, so every base is tried bare before any of them gets a suffix.
+///
+/// Templates, parameters and words are checked when the candidates are built. Whether a whole
+/// name is a valid, non-reserved identifier within the length bound depends on its suffix, e.g.
+/// `uint` + `8`, so searches run [`CandidateGenerator::check`] on their hits.
+///
+/// Searches only deal in indices, this turns an index back into the signature it stands for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidates {
+    /// Literal parts of each template around its placeholders, the last one ends with
+    /// `(parameters)`
+    bases: Vec<Vec<Vec<u8>>>,
+    /// `(template, parameters)` each base was built from
+    labels: Vec<(String, String)>,
+    suffix: Suffix,
+    /// Longest identifier allowed, see [`Candidates::max_name_len`]
+    max_len: Option<usize>,
+    /// Steps numeral suffixes in place, see [`CandidateGenerator::advance`]
+    odometer: Option<Odometer>,
+}
+
+impl Candidates {
+    pub fn new(template: &str, parameters: &str, alphabet: Alphabet) -> Result<Self, NameError> {
+        Self::from_bases([(template, parameters)], Suffix::Numeral { alphabet, width: None })
+    }
+
+    /// Decimal numbering, `name0`, `name1`, ...
+    pub fn decimal(template: &str, parameters: &str) -> Result<Self, NameError> {
+        Self::new(template, parameters, Alphabet::Decimal)
+    }
+
+    /// Word combinations, e.g. `poolGetOwner`, `poolGetFee`, ...
+    pub fn from_wordlists(
+        template: &str,
+        parameters: &str,
+        wordlists: Wordlists,
+    ) -> Result<Self, NameError> {
+        Self::from_bases([(template, parameters)], Suffix::Words(wordlists))
+    }
+
+    /// Interleaves `(template, parameters)` bases, [`NameError::NoNames`] if there are none
+    pub fn from_bases<'a>(
+        bases: impl IntoIterator<Item = (&'a str, &'a str)>,
+        suffix: Suffix,
+    ) -> Result<Self, NameError> {
+        let labels: Vec<(String, String)> = bases
+            .into_iter()
+            .map(|(template, parameters)| (template.to_string(), parameters.to_string()))
+            .collect();
+        if labels.is_empty() {
+            return Err(NameError::NoNames);
+        }
+        for (template, parameters) in &labels {
+            validate_base(template, parameters, &suffix)?;
+        }
+        if let Suffix::Words(wordlists) = &suffix {
+            let words = (0..wordlists.len()).flat_map(|list| wordlists.list(list));
+            if let Some(c) = words.flat_map(|word| word.chars()).find(|&c| !is_identifier_char(c)) {
+                return Err(NameError::InvalidCharacter(c));
+            }
+        }
+
+        let bases: Vec<Vec<Vec<u8>>> = labels
+            .iter()
+            .map(|(template, parameters)| {
+                let mut segments: Vec<Vec<u8>> =
+                    template.split(PLACEHOLDER).map(|segment| segment.into()).collect();
+                if segments.len() == 1 {
+                    segments.push(Vec::new());
+                }
+                segments
+                    .last_mut()
+                    .unwrap()
+                    .extend_from_slice(format!("({})", parameters).as_bytes());
+                segments
+            })
+            .collect();
+        let odometer = match &suffix {
+            Suffix::Numeral { alphabet, .. } => Some(alphabet.odometer()),
+            Suffix::Words(_) | Suffix::None => None,
+        };
+        Ok(Self { bases, labels, suffix, max_len: None, odometer })
+    }
+
+    /// Only accepts names of at most `max` characters. Numerals stop at the longest suffix that
+    /// still fits the shortest base, [`NameError::TooLong`] if no base fits at all.
+    pub fn max_name_len(self, max: usize) -> Result<Self, NameError> {
+        let shortest = (0..self.bases()).map(|base| self.base_len(base)).min().unwrap_or_default();
+        let len = shortest + self.width().unwrap_or_default() as usize;
+        if len > max {
+            return Err(NameError::TooLong { len, max });
+        }
+        Ok(Self { max_len: Some(max), ..self })
+    }
+
+    /// Longest identifier allowed, if bounded
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    /// Writes every numeral with exactly `width` symbols, `None` if `width` is 0 or more than
+    /// [`MAX_WIDTH`], or the suffix is not a numeral
+    pub fn fixed_width(self, width: u32) -> Option<Self> {
+        match self.suffix {
+            Suffix::Numeral { alphabet, width: _ } if (1..=MAX_WIDTH).contains(&width) => {
+                Some(Self { suffix: Suffix::Numeral { alphabet, width: Some(width) }, ..self })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn suffix(&self) -> &Suffix {
+        &self.suffix
+    }
+
+    pub fn width(&self) -> Option<u32> {
+        match self.suffix {
+            Suffix::Numeral { width, .. } => width,
+            Suffix::Words(_) | Suffix::None => None,
+        }
+    }
+
+    /// Number of bases taking turns
+    pub fn bases(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// Position of the base that produced the candidate at `index`
+    pub fn base(&self, index: u64) -> usize {
+        (index % self.bases.len() as u64) as usize
+    }
+
+    /// Identifier length of a base without any suffix, i.e. its template without placeholders
+    pub fn base_len(&self, base: usize) -> usize {
+        let (template, _) = &self.labels[base];
+        template.len() - template.matches(PLACEHOLDER).count() * PLACEHOLDER.len()
+    }
+
+    /// The same candidates restricted to one base, so its indices are suffix indices
+    pub fn base_only(&self, base: usize) -> Self {
+        Self {
+            bases: vec![self.bases[base].clone()],
+            labels: vec![self.labels[base].clone()],
+            suffix: self.suffix.clone(),
+            max_len: self.max_len,
+            odometer: self.odometer.clone(),
+        }
+    }
+
+    /// `(template, parameters)` of a base
+    pub fn label(&self, base: usize) -> (&str, &str) {
+        let (template, parameters) = &self.labels[base];
+        (template, parameters)
+    }
+
+    /// Word tuple a signature was built from, `None` unless the suffix is words
+    pub fn words(&self, index: u64) -> Option<Vec<&str>> {
+        match &self.suffix {
+            Suffix::Words(wordlists) => Some(wordlists.words(index / self.bases.len() as u64)),
+            Suffix::Numeral { .. } | Suffix::None => None,
+        }
+    }
+}
+
+impl CandidateGenerator for Candidates {
+    fn space(&self) -> Option<u64> {
+        let suffixes = match (&self.suffix, self.max_len) {
+            (Suffix::Numeral { width: None, .. }, Some(max)) => {
+                let shortest = (0..self.bases()).map(|base| self.base_len(base)).min()?;
+                // Suffixes past what fits the shortest base can not produce a valid name
+                let lengths = self.suffix.lengths()?;
+                match lengths.get(max.saturating_sub(shortest)) {
+                    Some((_, range)) if range.end != u64::MAX => Some(range.end),
+                    _ => None,
+                }
+            }
+            _ => self.suffix.space(),
+        };
+        suffixes?.checked_mul(self.bases.len() as u64)
+    }
+
+    #[inline]
+    fn write(&self, index: u64, buffer: &mut Vec<u8>) {
+        let (segments, index) = if self.bases.len() == 1 {
+            (&self.bases[0], index)
+        } else {
+            let bases = self.bases.len() as u64;
+            (&self.bases[(index % bases) as usize], index / bases)
+        };
+        let placeholders = segments.len() - 1;
+
+        buffer.clear();
+        buffer.extend_from_slice(&segments[0]);
+
+        match &self.suffix {
+            Suffix::Numeral { alphabet, width } => {
+                let mut digits = [0u8; 64];
+                let digits = match width {
+                    Some(width) => alphabet.padded_digits(index, *width as usize, &mut digits),
+                    None => alphabet.digits(index, &mut digits),
+                };
+                if placeholders == 1 {
+                    buffer.extend_from_slice(digits);
+                    buffer.extend_from_slice(&segments[1]);
+                    return;
+                }
+                for (placeholder, segment) in segments[1..].iter().enumerate() {
+                    // Digit `i` from the right goes to placeholder `i % placeholders`
+                    let first = (digits.len() + placeholders - 1 - placeholder) % placeholders;
+                    buffer.extend(digits.iter().skip(first).step_by(placeholders));
+                    buffer.extend_from_slice(segment);
+                }
+            }
+            Suffix::Words(wordlists) => {
+                for (placeholder, segment) in segments[1..].iter().enumerate() {
+                    // List `i` goes to placeholder `i % placeholders`
+                    for list in (placeholder..wordlists.len()).step_by(placeholders) {
+                        wordlists.write_word(index, list, buffer);
+                    }
+                    buffer.extend_from_slice(segment);
+                }
+            }
+            Suffix::None => {
+                for segment in &segments[1..] {
+                    buffer.extend_from_slice(segment);
+                }
+            }
+        }
+    }
+
+    /// Counts a numeral at the end of a single base up in place, growing it by one symbol when
+    /// the carry runs off its most significant digit
+    #[inline]
+    fn advance(&self, index: u64, buffer: &mut Vec<u8>) {
+        if let (Some(odometer), [segments]) = (&self.odometer, self.bases.as_slice())
+            && let [prefix, rest] = segments.as_slice()
+        {
+            let end = buffer.len() - rest.len();
+            if !odometer.increment(&mut buffer[prefix.len()..end]) {
+                return;
+            }
+            if self.width().is_none() {
+                buffer.insert(prefix.len(), odometer.one());
+                return;
+            }
+        }
+        // Interleaved bases, placeholders that share the digits, words, or past a fixed width
+        self.write(index, buffer);
+    }
+
+    /// The template before its first placeholder, if there is only one base
+    fn fixed_prefix(&self) -> usize {
+        match self.bases.as_slice() {
+            [segments] => segments[0].len(),
+            _ => 0,
+        }
+    }
+
+    fn render(&self, index: u64) -> String {
+        let mut buffer = Vec::new();
+        self.write(index, &mut buffer);
+        // Template and parameters come from `&str` and alphabets are ASCII
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// Also keeps names within [`Candidates::max_name_len`]
+    fn check(&self, signature: &[u8]) -> Result<(), NameError> {
+        let len = signature.iter().position(|&b| b == b'(').unwrap_or(signature.len());
+        if let Some(max) = self.max_len.filter(|&max| len > max) {
+            return Err(NameError::TooLong { len, max });
+        }
+        validate_signature(signature)
+    }
+}
+
+/// Checks what a base contributes to every name before any suffix is written
+fn validate_base(template: &str, parameters: &str, suffix: &Suffix) -> Result<(), NameError> {
+    validate_parameters(parameters)?;
+    if template.is_empty() {
+        return Err(NameError::Empty);
+    }
+    let name = template.replace(PLACEHOLDER, "");
+    match suffix {
+        // The base is the whole name
+        Suffix::None => validate_identifier(&name),
+        // A suffix may still make a reserved name valid, but never fix a leading digit
+        Suffix::Numeral { .. } | Suffix::Words(_) => {
+            if let Some(c) = name.chars().find(|&c| !is_identifier_char(c)) {
+                return Err(NameError::InvalidCharacter(c));
+            }
+            match template.chars().next() {
+                Some(first) if first.is_ascii_digit() => Err(NameError::InvalidStart(first)),
+                _ => Ok(()),
+            }?;
+            // Otherwise some names would start with a digit and the space would count them
+            if let Suffix::Numeral { alphabet, .. } = suffix
+                && template.starts_with(PLACEHOLDER)
+                && alphabet.symbols().iter().any(|symbol| symbol.is_ascii_digit())
+            {
+                return Err(NameError::LeadingDigits);
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CandidateGenerator, Candidates, Suffix};
+    use crate::{alphabet::Alphabet, identifier::NameError, wordlist::Wordlists};
+
+    #[test]
+    fn test_render() {
+        let candidates = Candidates::decimal("transfer", "address,uint256").unwrap();
+        assert_eq!(candidates.render(0), "transfer(address,uint256)");
+        assert_eq!(candidates.render(12345), "transfer12345(address,uint256)");
+
+        let candidates = Candidates::new("mint", "", Alphabet::Identifier).unwrap();
+        assert_eq!(candidates.render(64 + 63), "mint1$()");
+    }
+
+    #[test]
+    fn test_template() {
+        let candidates = Candidates::decimal("v{}_transfer", "address").unwrap();
+        assert_eq!(candidates.render(7), "v7_transfer(address)");
+        assert_eq!(Candidates::decimal("mint{}For", "").unwrap().render(42), "mint42For()");
+        assert_eq!(Candidates::decimal("transfer{}", "").unwrap().render(3), "transfer3()");
+    }
+
+    #[test]
+    fn test_several_placeholders() {
+        let candidates = Candidates::decimal("v{}_transfer{}", "").unwrap();
+        assert_eq!(candidates.render(0), "v_transfer()");
+        assert_eq!(candidates.render(9), "v9_transfer()");
+        assert_eq!(candidates.render(10), "v0_transfer1()");
+        assert_eq!(candidates.render(12345), "v135_transfer24()");
+
+        let candidates = Candidates::decimal("x{}a{}b{}", "").unwrap();
+        assert_eq!(candidates.render(1234), "x14a3b2()");
+
+        // Still one signature per index
+        let mut rendered: Vec<String> = (0..100_000).map(|i| candidates.render(i)).collect();
+        rendered.sort();
+        rendered.dedup();
+        assert_eq!(rendered.len(), 100_000);
+    }
+
+    #[test]
+    fn test_fixed_width() {
+        let candidates = Candidates::decimal("transfer_", "").unwrap().fixed_width(6).unwrap();
+        assert_eq!(candidates.render(0), "transfer_000000()");
+        assert_eq!(candidates.render(417), "transfer_000417()");
+        assert_eq!(candidates.space(), Some(1_000_000));
+        assert_eq!(candidates.range_end(None), 1_000_000);
+        assert_eq!(candidates.range_end(Some(10)), 10);
+
+        let candidates =
+            Candidates::new("mint{}", "", Alphabet::Hex).unwrap().fixed_width(2).unwrap();
+        assert_eq!(candidates.render(10), "mint0a()");
+        assert_eq!(candidates.space(), Some(256));
+
+        // Every candidate has the same length
+        let lengths: Vec<usize> = (0..256).map(|i| candidates.render(i).len()).collect();
+        assert!(lengths.iter().all(|&len| len == lengths[0]));
+
+        assert!(Candidates::decimal("mint", "").unwrap().fixed_width(0).is_none());
+        assert!(Candidates::decimal("mint", "").unwrap().fixed_width(65).is_none());
+        assert_eq!(Candidates::decimal("mint", "").unwrap().fixed_width(64).unwrap().space(), None);
+        assert_eq!(Candidates::decimal("mint", "").unwrap().space(), None);
+    }
+
+    #[test]
+    fn test_wordlists() {
+        let wordlists = Wordlists::new(vec![
+            vec!["get".into(), "set".into()],
+            vec!["owner".into(), "fee".into()],
+            vec!["now".into(), "later".into()],
+        ])
+        .unwrap();
+
+        let candidates = Candidates::from_wordlists("pool", "uint256", wordlists.clone()).unwrap();
+        assert_eq!(candidates.space(), Some(8));
+        assert_eq!(candidates.render(0), "poolGetOwnerNow(uint256)");
+        assert_eq!(candidates.render(5), "poolSetOwnerLater(uint256)");
+        assert_eq!(candidates.words(5), Some(vec!["set", "owner", "later"]));
+        assert!(candidates.clone().fixed_width(4).is_none());
+
+        let candidates = Candidates::from_wordlists("{}Pool{}", "", wordlists).unwrap();
+        assert_eq!(candidates.render(7), "setLaterPoolFee()");
+        assert_eq!(Candidates::decimal("pool", "").unwrap().words(7), None);
+        assert_eq!(candidates.suffix().render(7), "setFeeLater");
+    }
+
+    #[test]
+    fn test_names_and_parameters() {
+        let bases = [("deposit", "uint256"), ("depositFor", "address,uint256"), ("stake", "")];
+        let candidates = Candidates::from_bases(bases, Suffix::None).unwrap();
+        let rendered: Vec<String> = (0..3).map(|i| candidates.render(i)).collect();
+        assert_eq!(rendered, ["deposit(uint256)", "depositFor(address,uint256)", "stake()"]);
+
+        let candidates =
+            Candidates::from_bases(bases, Suffix::Numeral { alphabet: Alphabet::Hex, width: None })
+                .unwrap();
+        assert_eq!(candidates.render(3 * 0xff + 2), "stakeff()");
+        assert_eq!(candidates.label(candidates.base(3 * 0xff + 2)), ("stake", ""));
+    }
+
+    #[test]
+    fn test_lengths() {
+        let decimal = Suffix::Numeral { alphabet: Alphabet::Decimal, width: None };
+        let lengths = decimal.lengths().unwrap();
+        assert_eq!(lengths[..3], [(0, 0..1), (1, 1..10), (2, 10..100)]);
+        assert_eq!(lengths.len(), 21);
+        assert_eq!(lengths[20], (20, 10u64.pow(19)..u64::MAX));
+
+        let hex = Suffix::Numeral { alphabet: Alphabet::Hex, width: Some(3) };
+        assert_eq!(hex.lengths(), Some(vec![(3, 0..4096)]));
+        assert_eq!(Suffix::None.lengths(), Some(vec![(0, 0..1)]));
+
+        let candidates =
+            Candidates::from_bases([("v{}_mint", ""), ("stake", "")], decimal).unwrap();
+        assert_eq!(candidates.base_len(0), 6);
+        assert_eq!(candidates.base_only(1).render(12), "stake12()");
+    }
+
+    #[test]
+    fn test_bases() {
+        let variants = ["getBalance", "get_balance", "GetBalance"];
+        let bases = variants.iter().map(|name| (*name, "address"));
+        let candidates = Candidates::from_bases(bases.clone(), Suffix::None).unwrap();
+        assert_eq!(candidates.bases(), 3);
+        assert_eq!(candidates.space(), Some(3));
+        assert_eq!(candidates.render(1), "get_balance(address)");
+
+        // Every base bare first, then with suffixes in turn
+        let suffix = Suffix::Numeral { alphabet: Alphabet::Decimal, width: None };
+        let candidates = Candidates::from_bases(bases, suffix).unwrap();
+        assert_eq!(candidates.space(), None);
+        assert_eq!(candidates.render(2), "GetBalance(address)");
+        assert_eq!(candidates.render(3), "getBalance1(address)");
+        assert_eq!(candidates.render(3 * 42 + 1), "get_balance42(address)");
+        assert_eq!(candidates.base(3 * 42 + 1), 1);
+        assert_eq!(candidates.label(1), ("get_balance", "address"));
+
+        let candidates = candidates.fixed_width(2).unwrap();
+        assert_eq!(candidates.space(), Some(300));
+        assert_eq!(candidates.render(299), "GetBalance99(address)");
+
+        assert_eq!(Candidates::from_bases([], Suffix::None), Err(NameError::NoNames));
+        assert!(
+            Candidates::from_bases([("mint", "")], Suffix::None).unwrap().fixed_width(2).is_none()
+        );
+    }
+
+    #[test]
+    fn test_advance() {
+        let decimal = || Suffix::Numeral { alphabet: Alphabet::Decimal, width: None };
+        let all = [
+            Candidates::decimal("transfer", "address,uint256").unwrap(),
+            Candidates::new("mint", "", Alphabet::Identifier).unwrap(),
+            Candidates::decimal("v{}_transfer{}", "").unwrap(),
+            Candidates::decimal("tag_", "").unwrap().fixed_width(3).unwrap(),
+            Candidates::from_bases([("mint", ""), ("stake", "uint256")], decimal()).unwrap(),
+        ];
+        for candidates in all {
+            let mut buffer = Vec::new();
+            candidates.write(5, &mut buffer);
+            for index in 6..candidates.range_end(Some(5000)) {
+                candidates.advance(index, &mut buffer);
+                assert_eq!(buffer, candidates.render(index).as_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            Candidates::decimal("get-balance", "").unwrap_err(),
+            NameError::InvalidCharacter('-')
+        );
+        // Some suffixes in these alphabets would start the name with a digit
+        assert_eq!(Candidates::decimal("{}mint", "").unwrap_err(), NameError::LeadingDigits);
+        let hex = Suffix::Numeral { alphabet: Alphabet::Hex, width: None };
+        assert_eq!(
+            Candidates::from_bases([("{}mint", "")], hex).unwrap_err(),
+            NameError::LeadingDigits
+        );
+        let base62 = Suffix::Numeral { alphabet: Alphabet::Base62, width: None };
+        assert_eq!(
+            Candidates::from_bases([("{}mint", "")], base62).unwrap_err(),
+            NameError::LeadingDigits
+        );
+        let letters = Suffix::Numeral { alphabet: Alphabet::Custom(b"xyz".to_vec()), width: None };
+        let candidates = Candidates::from_bases([("{}mint", "")], letters).unwrap();
+        assert_eq!(candidates.render(4), "yymint()");
+        assert!(Candidates::decimal("m{}int", "").is_ok());
+        assert_eq!(
+            Candidates::decimal("mint", "address, uint256").unwrap_err(),
+            NameError::InvalidParameter(' ')
+        );
+        // A reserved name is only fatal if nothing is ever appended
+        assert!(Candidates::decimal("delete", "").is_ok());
+        assert_eq!(
+            Candidates::from_bases([("delete", "")], Suffix::None).unwrap_err(),
+            NameError::Reserved("delete".to_string())
+        );
+
+        let wordlists = Wordlists::new(vec![vec!["get".into(), "set-up".into()]]).unwrap();
+        assert_eq!(
+            Candidates::from_wordlists("pool", "", wordlists).unwrap_err(),
+            NameError::InvalidCharacter('-')
+        );
+    }
+}
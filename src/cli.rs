@@ -1,24 +1,184 @@
 use gumdrop::Options;
 
+use crate::{alphabet::Alphabet, backend::Backend, pattern::Anchor, score::Objective};
+
 // CLI Options
 #[derive(Debug, Options, Clone)]
 pub struct Opts {
     pub help: bool,
-    #[options(help = "Desired pattern, e.g., \"0x01234\"", required, short = "x", meta = "")]
-    pub pattern: String,
     #[options(
-        help = "Function name, e.g., \"checkAddressInfo\"",
+        help = "Desired pattern, e.g., \"0x01234\" or \"0x12??56?8\" (? or x is any nibble)",
+        short = "x",
+        meta = ""
+    )]
+    pub pattern: Option<String>,
+    #[options(
+        help = "Where the pattern sits: prefix (default), suffix, anywhere or a nibble offset",
+        short = "a",
+        meta = "",
+        parse(try_from_str)
+    )]
+    pub anchor: Option<Anchor>,
+    #[options(
+        help = "Bit mask applied to the selector instead of a pattern, e.g., \"0xffe00000\"",
+        no_short,
+        meta = "",
+        parse(try_from_str = "crate::pattern::parse_selector")
+    )]
+    pub mask: Option<u32>,
+    #[options(
+        help = "Value the masked selector must equal (default: 0x00000000)",
+        no_short,
+        meta = "",
+        parse(try_from_str = "crate::pattern::parse_selector")
+    )]
+    pub value: Option<u32>,
+    #[options(
+        help = "Selector must be below this value, e.g., \"0x01000000\"",
+        no_short,
+        meta = "",
+        parse(try_from_str = "crate::pattern::parse_selector")
+    )]
+    pub below: Option<u32>,
+    #[options(
+        help = "Selector must be above this value, e.g., \"0xff000000\"",
+        no_short,
+        meta = "",
+        parse(try_from_str = "crate::pattern::parse_selector")
+    )]
+    pub above: Option<u32>,
+    #[options(
+        help = "Selector must be in this inclusive range, e.g., \"0x10000000 0x1fffffff\"",
+        no_short,
+        meta = "",
+        parse(try_from_str = "crate::pattern::parse_selector")
+    )]
+    pub between: Option<(u32, u32)>,
+    #[options(
+        help = "File with acceptable selectors, one per line, any of them is a match",
+        no_short,
+        meta = ""
+    )]
+    pub targets_file: Option<String>,
+    #[options(
+        help = "Accept any selector with at least this many zero bytes (cheaper calldata)",
+        no_short,
+        meta = ""
+    )]
+    pub zero_bytes: Option<u32>,
+    #[options(
+        help = "Match expression, e.g., \"prefix(00) & (suffix(ff) | below(0x00100000))\"",
+        short = "e",
+        meta = ""
+    )]
+    pub expr: Option<String>,
+    #[options(help = "File containing a match expression", no_short, meta = "")]
+    pub expr_file: Option<String>,
+    #[options(
+        help = "Selectors never to return: exact, patterns or VALUE/MASK, comma separated, \
+                e.g., \"0x00000000,0xffffffff,0xdead\"",
+        no_short,
+        meta = ""
+    )]
+    pub deny: Option<String>,
+    #[options(help = "File with deny list entries, one or more per line", no_short, meta = "")]
+    pub deny_file: Option<String>,
+    #[options(
+        help = "Run for --time-budget and report the best selectors by leading-zero-bits, \
+                zero-bytes or smallness instead of matching a target",
+        no_short,
+        meta = "",
+        parse(try_from_str)
+    )]
+    pub optimize: Option<Objective>,
+    #[options(help = "Seconds to optimize for (default: 600)", no_short, meta = "")]
+    pub time_budget: Option<u64>,
+    #[options(help = "Number of best candidates to report (default: 10)", no_short, meta = "")]
+    pub top: Option<usize>,
+    #[options(
+        help = "Function name or template, e.g., \"checkAddressInfo\" or \"v{}_transfer\" \
+                ({} is where the varying part goes, the end by default), comma separated to try \
+                several, e.g., \"deposit,depositFor,stake\"",
         required,
         short = "f",
         meta = ""
     )]
     pub fn_name: String,
     #[options(
-        help = "Optional function parameters e.g., \"address,address,uint256\"",
+        help = "Optional function parameters e.g., \"address,address,uint256\", separate \
+                several lists with ;, e.g., \"uint256;address,uint256\"",
         short = "p",
         meta = ""
     )]
     pub fn_parameters: Option<String>,
+    #[options(
+        help = "Suffix alphabet: decimal (default), hex, base36, base62, identifier \
+                ([0-9a-zA-Z_$]) or custom:SYMBOLS",
+        no_short,
+        meta = "",
+        parse(try_from_str)
+    )]
+    pub alphabet: Option<Alphabet>,
+    #[options(
+        help = "Write the varying part with exactly this many symbols, zero-padded, and stop \
+                once all of them are tried",
+        no_short,
+        meta = ""
+    )]
+    pub width: Option<u32>,
+    #[options(
+        help = "Build names from words instead, one word per line, repeat for each list, e.g., \
+                \"--wordlist verbs.txt --wordlist nouns.txt\"",
+        no_short,
+        meta = ""
+    )]
+    pub wordlist: Vec<String>,
+    #[options(
+        help = "Also try style variants of the function name: camelCase, PascalCase, \
+                snake_case, flatcase, SCREAMING_SNAKE and a trailing underscore",
+        no_short
+    )]
+    pub styles: bool,
+    #[options(
+        help = "Words --styles may put in front of the name, comma separated, e.g., \"try,do\"",
+        no_short,
+        meta = ""
+    )]
+    pub prefix_words: Option<String>,
+    #[options(
+        help = "Words --styles may append to the name, comma separated, e.g., \"of,for\"",
+        no_short,
+        meta = ""
+    )]
+    pub suffix_words: Option<String>,
+    #[options(help = "Only try the names themselves, without a varying suffix", no_short)]
+    pub no_suffix: bool,
+    #[options(
+        help = "Search every name length completely before the next one and report the \
+                shortest matching name",
+        no_short
+    )]
+    pub shortest: bool,
+    #[options(
+        help = "Longest function name to accept, not counting the parameters, e.g., \"8\"",
+        no_short,
+        meta = ""
+    )]
+    pub max_name_len: Option<usize>,
+    #[options(
+        help = "Find one suffix that works for every name and parameter list at once, give one \
+                pattern for all or one per signature separated by \";\", e.g., \"00;11\"",
+        no_short
+    )]
+    pub joint: bool,
+    #[options(
+        help = "Keccak-256 implementation that hashes the search and verifies matches: asm or \
+                portable, if compiled in (default: portable on a CPU with AVX2, asm otherwise)",
+        no_short,
+        meta = "",
+        parse(try_from_str)
+    )]
+    pub backend: Option<Backend>,
     #[options(
         help = "Number of threads to use (default: number of physical cores)",
         short = "t",
@@ -0,0 +1,112 @@
+use std::{io, path::Path};
+
+use crate::{
+    pattern::{Pattern, parse_selector},
+    selector_set::SelectorSet,
+};
+
+/// Selectors a search must never return, e.g. clashes with existing or proxy admin functions.
+///
+/// Entries are exact selectors (`0xa9059cbb`), patterns (`0xdead`, `0x12??56??`) or bit masks
+/// written as `VALUE/MASK` (`0x00000000/0xff000000`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenyList {
+    exact: SelectorSet,
+    patterns: Vec<Pattern>,
+}
+
+impl Default for DenyList {
+    fn default() -> Self {
+        Self { exact: SelectorSet::new([]), patterns: Vec::new() }
+    }
+}
+
+impl DenyList {
+    /// Parses entries separated by commas, whitespace or new lines.
+    ///
+    /// Everything after a `#` on a line is ignored.
+    pub fn parse(entries: &str) -> io::Result<Self> {
+        let mut exact = Vec::new();
+        let mut patterns = Vec::new();
+
+        let entries = entries
+            .lines()
+            .flat_map(|line| line.split('#').next().unwrap_or_default().split([',', ' ', '\t']))
+            .filter(|entry| !entry.is_empty());
+        for entry in entries {
+            let invalid = |e: String| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{:?}: {}", entry, e))
+            };
+            let pattern = match entry.split_once('/') {
+                Some((value, mask)) => {
+                    let value = parse_selector(value).map_err(|e| invalid(e.to_string()))?;
+                    let mask = parse_selector(mask).map_err(|e| invalid(e.to_string()))?;
+                    Pattern::from_mask_value(mask, value).map_err(|e| invalid(e.to_string()))?
+                }
+                None => Pattern::parse(entry).map_err(|e| invalid(e.to_string()))?,
+            };
+
+            if pattern.mask() == u32::MAX {
+                exact.push(pattern.value());
+            } else {
+                patterns.push(pattern);
+            }
+        }
+
+        Ok(Self { exact: SelectorSet::new(exact), patterns })
+    }
+
+    /// Reads entries from a file, see [`DenyList::parse`]
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    #[inline]
+    pub fn denies(&self, selector: u32) -> bool {
+        self.exact.contains(selector) || self.patterns.iter().any(|p| p.matches(selector))
+    }
+
+    pub fn len(&self) -> usize {
+        self.exact.len() + self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DenyList;
+
+    #[test]
+    fn test_parse() {
+        let deny_list = DenyList::parse(
+            "0x00000000, 0xffffffff # reserved\n\
+             0xdead # prefix\n\
+             0x12??56?? 0x00000000/0xffe00000\n",
+        )
+        .unwrap();
+        assert_eq!(deny_list.len(), 5);
+
+        assert!(deny_list.denies(0x00000000));
+        assert!(deny_list.denies(0xffffffff));
+        assert!(deny_list.denies(0xdead1234));
+        assert!(deny_list.denies(0x12345678));
+        assert!(deny_list.denies(0x001fffff));
+
+        assert!(!deny_list.denies(0x00200000));
+        assert!(!deny_list.denies(0xfffffffe));
+        assert!(!deny_list.denies(0x13345678));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(DenyList::parse("0x1234567890").is_err());
+        assert!(DenyList::parse("0xnope").is_err());
+        assert!(DenyList::parse("0x00000001/0xff000000").is_err());
+        assert!(DenyList::parse("0x01000000/0xff000000").is_ok());
+        assert!(DenyList::default().is_empty());
+    }
+}
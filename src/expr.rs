@@ -0,0 +1,397 @@
+//! Match expressions combining several constraints into one [`Target`].
+//!
+//! ```text
+//! prefix(00) & !in(clashes.txt) & (suffix(ff) | below(0x00100000))
+//! ```
+//!
+//! Operators are `&`/`and`, `|`/`or` and `!`/`not`, with the usual precedence and parentheses for
+//! grouping. Predicates:
+//!
+//! | Predicate | Matches |
+//! |-----------|---------|
+//! | `prefix(P)`, `suffix(P)`, `anywhere(P)` | Pattern `P` at the start, end or any offset |
+//! | `at(N, P)` | Pattern `P` at nibble offset `N` |
+//! | `mask(MASK, VALUE)` | `(selector & MASK) == VALUE`, `VALUE` defaults to zero |
+//! | `below(X)`, `above(X)`, `between(LOW, HIGH)` | Selector as a big-endian number |
+//! | `set(S, ...)` | Any of the listed selectors |
+//! | `in(PATH)` | Any selector listed in the file, see [`SelectorSet::from_file`] |
+//! | `zeros(N)` | At least `N` zero bytes |
+//!
+//! Arguments that contain spaces or operator characters can be quoted with `"`.
+
+use std::fmt;
+
+use crate::{
+    pattern::{Anchor, Pattern, parse_selector},
+    selector_set::SelectorSet,
+    target::Target,
+};
+
+/// Parse failure with the byte offset in the expression it was detected at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at position {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    LeftParen,
+    RightParen,
+    Comma,
+    And,
+    Or,
+    Not,
+}
+
+/// How deep `!` and parentheses can nest, which bounds the parser's recursion
+pub const MAX_DEPTH: usize = 64;
+
+/// Parses a match expression into a [`Target`] tree
+pub fn parse_expression(expression: &str) -> Result<Target, ExprError> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser { tokens, next: 0, end: expression.len(), depth: 0 };
+    let target = parser.parse_or()?;
+    match parser.tokens.get(parser.next) {
+        None => Ok(target),
+        Some((position, token)) => {
+            Err(ExprError { position: *position, message: format!("unexpected {:?}", token) })
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '#' => {
+                // Comment until the end of the line, handy in expression files
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                continue;
+            }
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            ',' => Token::Comma,
+            '&' => Token::And,
+            '|' => Token::Or,
+            '!' => Token::Not,
+            '"' => {
+                chars.next();
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => word.push(c),
+                        None => {
+                            return Err(ExprError {
+                                position,
+                                message: "unterminated quote".to_string(),
+                            });
+                        }
+                    }
+                }
+                tokens.push((position, Token::Word(word)));
+                continue;
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some((_, c)) =
+                    chars.next_if(|&(_, c)| !c.is_whitespace() && !"()!&|,\"#".contains(c))
+                {
+                    word.push(c);
+                }
+                let token = match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Word(word),
+                };
+                tokens.push((position, token));
+                continue;
+            }
+        };
+        chars.next();
+        tokens.push((position, token));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    end: usize,
+    /// `!` and parentheses the parser is currently inside
+    depth: usize,
+}
+
+impl Parser {
+    fn position(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.end, |(position, _)| *position)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ExprError> {
+        Err(ExprError { position: self.position(), message: message.into() })
+    }
+
+    fn accept(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.next).is_some_and(|(_, next)| next == token) {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), ExprError> {
+        if self.accept(token) { Ok(()) } else { self.error(format!("expected {:?}", token)) }
+    }
+
+    fn parse_or(&mut self) -> Result<Target, ExprError> {
+        let mut targets = vec![self.parse_and()?];
+        while self.accept(&Token::Or) {
+            targets.push(self.parse_and()?);
+        }
+        Ok(if targets.len() == 1 { targets.pop().unwrap() } else { Target::Any(targets) })
+    }
+
+    fn parse_and(&mut self) -> Result<Target, ExprError> {
+        let mut targets = vec![self.parse_unary()?];
+        while self.accept(&Token::And) {
+            targets.push(self.parse_unary()?);
+        }
+        Ok(if targets.len() == 1 { targets.pop().unwrap() } else { Target::All(targets) })
+    }
+
+    fn parse_unary(&mut self) -> Result<Target, ExprError> {
+        if self.accept(&Token::Not) {
+            return self.nested(|parser| Ok(Target::Not(Box::new(parser.parse_unary()?))));
+        }
+        if self.accept(&Token::LeftParen) {
+            return self.nested(|parser| {
+                let target = parser.parse_or()?;
+                parser.expect(&Token::RightParen)?;
+                Ok(target)
+            });
+        }
+        self.parse_predicate()
+    }
+
+    /// Runs `parse` one level deeper, failing past [`MAX_DEPTH`] instead of overflowing the stack
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Target, ExprError>,
+    ) -> Result<Target, ExprError> {
+        if self.depth == MAX_DEPTH {
+            // At the `!` or `(` just accepted
+            let position = self.tokens[self.next - 1].0;
+            let message = format!("nested deeper than {} levels", MAX_DEPTH);
+            return Err(ExprError { position, message });
+        }
+        self.depth += 1;
+        let target = parse(self);
+        self.depth -= 1;
+        target
+    }
+
+    fn parse_predicate(&mut self) -> Result<Target, ExprError> {
+        let position = self.position();
+        let Some((_, Token::Word(name))) = self.tokens.get(self.next).cloned() else {
+            return self.error("expected a predicate");
+        };
+        self.next += 1;
+
+        self.expect(&Token::LeftParen)?;
+        let mut args = Vec::new();
+        if !self.accept(&Token::RightParen) {
+            loop {
+                match self.tokens.get(self.next).cloned() {
+                    Some((_, Token::Word(arg))) => args.push(arg),
+                    _ => return self.error("expected an argument"),
+                }
+                self.next += 1;
+                if self.accept(&Token::RightParen) {
+                    break;
+                }
+                self.expect(&Token::Comma)?;
+            }
+        }
+
+        predicate(&name, &args).map_err(|message| ExprError { position, message })
+    }
+}
+
+/// Builds the target for a single predicate
+fn predicate(name: &str, args: &[String]) -> Result<Target, String> {
+    let arity = |expected: std::ops::RangeInclusive<usize>| {
+        if expected.contains(&args.len()) {
+            Ok(())
+        } else {
+            Err(format!("{}() takes {:?} arguments, got {}", name, expected, args.len()))
+        }
+    };
+    let selector = |i: usize| {
+        parse_selector(&args[i]).map_err(|e| format!("invalid selector {:?}: {}", args[i], e))
+    };
+    let pattern = |i: usize, anchor: Anchor| {
+        Pattern::parse_anchored(&args[i], anchor)
+            .map(Target::Pattern)
+            .map_err(|e| format!("invalid pattern {:?}: {}", args[i], e))
+    };
+    let number = |i: usize| {
+        args[i].parse::<u32>().map_err(|e| format!("invalid number {:?}: {}", args[i], e))
+    };
+    let empty = || format!("{}() matches no selector", name);
+
+    match name {
+        "prefix" => arity(1..=1).and_then(|_| pattern(0, Anchor::Prefix)),
+        "suffix" => arity(1..=1).and_then(|_| pattern(0, Anchor::Suffix)),
+        "anywhere" => arity(1..=1).and_then(|_| pattern(0, Anchor::Anywhere)),
+        "at" => {
+            arity(2..=2)?;
+            pattern(1, Anchor::Offset(number(0)?))
+        }
+        "mask" => {
+            arity(1..=2)?;
+            let value = if args.len() == 2 { selector(1)? } else { 0 };
+            Pattern::from_mask_value(selector(0)?, value)
+                .map(Target::Pattern)
+                .map_err(|e| e.to_string())
+        }
+        "below" => arity(1..=1).and_then(|_| Target::below(selector(0)?).ok_or_else(empty)),
+        "above" => arity(1..=1).and_then(|_| Target::above(selector(0)?).ok_or_else(empty)),
+        "between" => {
+            arity(2..=2)?;
+            Target::between(selector(0)?, selector(1)?).ok_or_else(empty)
+        }
+        "set" => {
+            let selectors = (0..args.len()).map(selector).collect::<Result<Vec<_>, _>>()?;
+            let set = SelectorSet::new(selectors);
+            if set.is_empty() { Err(empty()) } else { Ok(Target::Set(set)) }
+        }
+        "in" => {
+            arity(1..=1)?;
+            let set = SelectorSet::from_file(&args[0])
+                .map_err(|e| format!("failed to read {}: {}", args[0], e))?;
+            if set.is_empty() { Err(empty()) } else { Ok(Target::Set(set)) }
+        }
+        "zeros" => arity(1..=1).and_then(|_| {
+            Target::zero_bytes(number(0)?).ok_or_else(|| "a selector has only 4 bytes".to_string())
+        }),
+        _ => Err(format!("unknown predicate {:?}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExprError, MAX_DEPTH, parse_expression};
+    use crate::{pattern::Pattern, target::Target};
+
+    #[test]
+    fn test_precedence() {
+        // `&` binds tighter than `|`, `!` tighter than both
+        let target = parse_expression("prefix(00) & !set(0x00000000) | suffix(ff)").unwrap();
+        let Target::Any(any) = &target else { panic!("expected any, got {:?}", target) };
+        assert!(matches!(&any[0], Target::All(all) if matches!(all[1], Target::Not(_))));
+
+        assert!(target.matches(0x00123456));
+        assert!(!target.matches(0x00000000));
+        assert!(target.matches(0x123456ff));
+        assert!(!target.matches(0x12345678));
+    }
+
+    #[test]
+    fn test_request_example() {
+        // Prefix 00, not any of these, and either ends with ff or is below 0x00100000
+        let target = parse_expression(
+            "prefix(00) and not set(0x000000ff, 0x00000001) \
+             and (suffix(ff) or below(0x00100000))",
+        )
+        .unwrap();
+        assert!(target.matches(0x001234ff));
+        assert!(target.matches(0x00012345));
+        assert!(!target.matches(0x000000ff));
+        assert!(!target.matches(0x00000001));
+        assert!(!target.matches(0x00123456));
+        assert!(!target.matches(0x01000000));
+    }
+
+    #[test]
+    fn test_predicates() {
+        let matches = |expression: &str, selector: u32| {
+            parse_expression(expression).unwrap().matches(selector)
+        };
+        assert!(matches("anywhere(beef)", 0x12beef34));
+        assert!(matches("at(2, be?f)", 0x12be3f34));
+        assert!(matches("mask(0xe0000000)", 0x1fffffff));
+        assert!(matches("mask(0x1f, 0x16)", 0xabcdef16));
+        assert!(matches("above(0xfffffff0)", 0xffffffff));
+        assert!(matches("between(0x10000000, 0x1fffffff)", 0x1abcdef0));
+        assert!(matches("zeros(2)", 0x12000034));
+        assert!(!matches("zeros(2)", 0x12003456));
+        assert!(matches("((prefix(12)))", 0x12345678));
+
+        assert_eq!(parse_expression("prefix(12)"), Ok(Pattern::parse("12").unwrap().into()));
+    }
+
+    #[test]
+    fn test_difficulty() {
+        let target = parse_expression("prefix(00) & suffix(ff)").unwrap();
+        assert_eq!(target.difficulty(), 65536.0);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |expression: &str| parse_expression(expression).unwrap_err();
+
+        assert_eq!(error("prefix(12) &").position, 12);
+        assert_eq!(error("prefix(12) suffix(34)").position, 11);
+        assert_eq!(error("(prefix(12)").message, "expected RightParen");
+        assert_eq!(
+            error("nope(12)"),
+            ExprError { position: 0, message: "unknown predicate \"nope\"".to_string() }
+        );
+        assert!(error("prefix(12, 34)").message.contains("arguments"));
+        assert!(error("prefix(123456789)").message.contains("invalid pattern"));
+        assert!(error("below(0)").message.contains("matches no selector"));
+        assert_eq!(error("set()").message, "set() matches no selector");
+        assert_eq!(error("!set( )").position, 1);
+        assert!(error("in(\"/does/not exist\")").message.starts_with("failed to read"));
+
+        // Same as an empty --targets-file
+        let path = std::env::temp_dir().join(format!("vanity-4b-empty-{}.txt", std::process::id()));
+        std::fs::write(&path, "# nothing yet\n").unwrap();
+        let message = error(&format!("in({:?})", path.to_str().unwrap())).message;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(message, "in() matches no selector");
+    }
+
+    #[test]
+    fn test_nesting() {
+        let nested =
+            |depth: usize| format!("{}prefix(12){}", "!(".repeat(depth / 2), ")".repeat(depth / 2));
+        let target = parse_expression(&nested(MAX_DEPTH)).unwrap();
+        assert!(target.matches(0x12345678));
+
+        let error = parse_expression(&nested(MAX_DEPTH + 2)).unwrap_err();
+        assert_eq!(error.message, format!("nested deeper than {} levels", MAX_DEPTH));
+        // Deep enough to overflow the stack without the bound
+        let error = parse_expression(&"!".repeat(1_000_000)).unwrap_err();
+        assert_eq!(error.position, MAX_DEPTH);
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+
+/// Keywords, reserved words and builtins a function can not be named after
+#[rustfmt::skip]
+const RESERVED: &[&str] = &[
+    // Keywords
+    "abstract", "address", "anonymous", "as", "assembly", "bool", "break", "byte", "bytes",
+    "calldata", "catch", "constant", "constructor", "continue", "contract", "delete", "do", "else",
+    "emit", "enum", "event", "external", "fallback", "false", "for", "function", "global",
+    "hex", "if", "immutable", "import", "indexed", "interface", "internal", "is", "library",
+    "mapping", "memory", "modifier", "new", "override", "payable", "pragma", "private", "public",
+    "pure", "receive", "return", "returns", "storage", "string", "struct", "transient", "true",
+    "try", "type", "unchecked", "unicode", "using", "view", "virtual", "while",
+    // Reserved for future use
+    "after", "alias", "apply", "auto", "case", "copyof", "default", "define", "final",
+    "implements", "in", "inline", "let", "macro", "match", "mutable", "null", "of", "partial",
+    "promise", "reference", "relocatable", "sealed", "sizeof", "static", "supports", "switch",
+    "typedef", "typeof",
+    // Units
+    "wei", "gwei", "ether", "seconds", "minutes", "hours", "days", "weeks", "years",
+    // Builtins
+    "abi", "addmod", "assert", "blobhash", "block", "blockhash", "ecrecover", "gasleft",
+    "keccak256", "msg", "mulmod", "now", "require", "revert", "ripemd160", "selfdestruct", "sha256",
+    "sha3", "suicide", "super", "this", "tx",
+];
+
+/// Reasons a name can not be a Solidity function name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    /// No function name at all
+    Empty,
+    /// No names to search were given
+    NoNames,
+    /// Identifiers can not start with a digit
+    InvalidStart(char),
+    /// Placeholder at the start of a template whose alphabet has digits, so some names would
+    /// start with one
+    LeadingDigits,
+    /// Character outside `[a-zA-Z0-9_$]`
+    InvalidCharacter(char),
+    /// Keyword, reserved word or builtin
+    Reserved(String),
+    /// Longer than the allowed maximum
+    TooLong { len: usize, max: usize },
+    /// Character that can not appear in a parameter type list
+    InvalidParameter(char),
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "function name is empty"),
+            NameError::NoNames => write!(f, "no function names given"),
+            NameError::InvalidStart(c) => write!(f, "identifier can not start with {:?}", c),
+            NameError::LeadingDigits => {
+                write!(f, "names could start with a digit, move {{}} after the first letter")
+            }
+            NameError::InvalidCharacter(c) => {
+                write!(f, "character {:?} is not allowed in an identifier", c)
+            }
+            NameError::Reserved(name) => write!(f, "{:?} is a reserved word or builtin", name),
+            NameError::TooLong { len, max } => {
+                write!(f, "name is {} characters long, at most {} allowed", len, max)
+            }
+            NameError::InvalidParameter(c) => {
+                write!(f, "character {:?} is not allowed in a parameter list", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+/// Whether `c` may appear in an identifier, `[a-zA-Z0-9_$]`
+#[inline]
+pub fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Keywords, reserved words, units, builtins and elementary type names such as `uint256`
+pub fn is_reserved(name: &str) -> bool {
+    if RESERVED.contains(&name) {
+        return true;
+    }
+
+    // Sized elementary types, `int8`..`int256`, `bytes1`..`bytes32`, `fixed128x18`, ...
+    let sized = |prefix: &str, valid: &dyn Fn(u32) -> bool| {
+        name.strip_prefix(prefix)
+            .filter(|size| !size.starts_with('0'))
+            .and_then(|size| size.parse::<u32>().ok())
+            .is_some_and(valid)
+    };
+    let bits = |size: u32| size.is_multiple_of(8) && (8..=256).contains(&size);
+    if ["int", "uint"].iter().any(|prefix| name == *prefix || sized(prefix, &bits))
+        || sized("bytes", &|size| (1..=32).contains(&size))
+    {
+        return true;
+    }
+    ["fixed", "ufixed"].iter().any(|prefix| {
+        name == *prefix
+            || name.strip_prefix(prefix).and_then(|size| size.split_once('x')).is_some_and(
+                |(m, n)| {
+                    m.parse::<u32>().is_ok_and(bits) && n.parse::<u32>().is_ok_and(|n| n <= 80)
+                },
+            )
+    })
+}
+
+/// Checks that `name` is a Solidity identifier that is not reserved
+pub fn validate_identifier(name: &str) -> Result<(), NameError> {
+    let first = name.chars().next().ok_or(NameError::Empty)?;
+    if let Some(c) = name.chars().find(|&c| !is_identifier_char(c)) {
+        return Err(NameError::InvalidCharacter(c));
+    }
+    if first.is_ascii_digit() {
+        return Err(NameError::InvalidStart(first));
+    }
+    if is_reserved(name) {
+        return Err(NameError::Reserved(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Checks the name of a signature such as `transfer(address,uint256)`, everything before the
+/// first `(`
+pub fn validate_signature(signature: &[u8]) -> Result<(), NameError> {
+    let name = signature.split(|&b| b == b'(').next().unwrap_or_default();
+    match std::str::from_utf8(name) {
+        Ok(name) => validate_identifier(name),
+        // Not ASCII, so not an identifier either
+        Err(_) => Err(NameError::InvalidCharacter(char::REPLACEMENT_CHARACTER)),
+    }
+}
+
+/// Checks the characters of a comma separated parameter type list, e.g. `address,uint256[2]`.
+///
+/// Whitespace is rejected as well because it would change the signature that gets hashed.
+pub fn validate_parameters(parameters: &str) -> Result<(), NameError> {
+    match parameters.chars().find(|&c| !(is_identifier_char(c) || ",[]()".contains(c))) {
+        Some(c) => Err(NameError::InvalidParameter(c)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        NameError, is_reserved, validate_identifier, validate_parameters, validate_signature,
+    };
+
+    #[test]
+    fn test_validate_identifier() {
+        assert_eq!(validate_identifier("transfer"), Ok(()));
+        assert_eq!(validate_identifier("_$v2"), Ok(()));
+        assert_eq!(validate_identifier(""), Err(NameError::Empty));
+        assert_eq!(validate_identifier("123"), Err(NameError::InvalidStart('1')));
+        assert_eq!(validate_identifier("get-balance"), Err(NameError::InvalidCharacter('-')));
+        assert_eq!(validate_identifier("transfér"), Err(NameError::InvalidCharacter('é')));
+        assert_eq!(validate_identifier("return"), Err(NameError::Reserved("return".to_string())));
+
+        assert_eq!(validate_signature(b"transfer(address,uint256)"), Ok(()));
+        assert_eq!(validate_signature(b"mint"), Ok(()));
+        assert_eq!(validate_signature(b"(uint256)"), Err(NameError::Empty));
+        assert_eq!(
+            validate_signature(b"byte(uint8)"),
+            Err(NameError::Reserved("byte".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_reserved() {
+        for name in ["function", "msg", "keccak256", "ether", "uint", "uint256", "int8", "bytes32"]
+        {
+            assert!(is_reserved(name), "{}", name);
+        }
+        for name in ["fixed", "ufixed128x18", "fixed8x0", "typeof"] {
+            assert!(is_reserved(name), "{}", name);
+        }
+        for name in ["uint7", "uint264", "uint08", "bytes33", "bytes0", "fixed8x81", "transfer"] {
+            assert!(!is_reserved(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_validate_parameters() {
+        assert_eq!(validate_parameters("address,uint256[2],(bool,bytes)[]"), Ok(()));
+        assert_eq!(validate_parameters(""), Ok(()));
+        assert_eq!(validate_parameters("address, uint256"), Err(NameError::InvalidParameter(' ')));
+    }
+}
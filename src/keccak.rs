@@ -0,0 +1,639 @@
+use std::fmt;
+
+/// Bytes absorbed per Keccak-256 block
+pub const RATE: usize = 136;
+
+/// Lanes of a block, the rest of the 25 lane state is capacity
+const RATE_LANES: usize = RATE / 8;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Lanes and rotations that rho and pi move into each row of the state, chi then mixes a row
+const ROWS: [[(usize, u32); 5]; 5] = [
+    [(0, 0), (6, 44), (12, 43), (18, 21), (24, 14)],
+    [(3, 28), (9, 20), (10, 3), (16, 45), (22, 61)],
+    [(1, 1), (7, 6), (13, 25), (19, 8), (20, 18)],
+    [(4, 27), (5, 36), (11, 10), (17, 15), (23, 56)],
+    [(2, 62), (8, 55), (14, 39), (15, 41), (21, 2)],
+];
+
+/// Theta of the state as the value xored into each column
+#[inline(always)]
+fn theta(a: &[u64; 25]) -> [u64; 5] {
+    let c: [u64; 5] = std::array::from_fn(|x| a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20]);
+    std::array::from_fn(|x| c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1))
+}
+
+/// One round from `a` into `e`, working out of place lets every step run on whole rows
+#[inline(always)]
+fn round(a: &[u64; 25], e: &mut [u64; 25], round_constant: u64) {
+    let d = theta(a);
+    for (y, row) in ROWS.iter().enumerate() {
+        let b = row.map(|(lane, rotation)| (a[lane] ^ d[lane % 5]).rotate_left(rotation));
+        for x in 0..5 {
+            e[5 * y + x] = b[x] ^ (!b[(x + 1) % 5] & b[(x + 2) % 5]);
+        }
+    }
+    e[0] ^= round_constant;
+}
+
+/// Keccak-f[1600] on `state`
+pub fn keccak_f1600(state: &mut [u64; 25]) {
+    let mut e = [0u64; 25];
+    for pair in ROUND_CONSTANTS.chunks_exact(2) {
+        round(state, &mut e, pair[0]);
+        round(&e, state, pair[1]);
+    }
+}
+
+/// Keccak-256 digest of `input`, the sponge over [`keccak_f1600`]
+#[cfg(feature = "portable")]
+pub fn keccak_256(input: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+    let whole = input.len() / RATE * RATE;
+    for block in input[..whole].chunks_exact(RATE) {
+        absorb(&mut state, block);
+    }
+    let mut last = Block::default();
+    last.load(&input[whole..], 0);
+    for (lane, word) in state.iter_mut().zip(last.lanes()) {
+        *lane ^= word;
+    }
+    keccak_f1600(&mut state);
+
+    let mut digest = [0u8; 32];
+    for (bytes, lane) in digest.chunks_exact_mut(8).zip(state) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
+
+/// Keccak-f[1600] that only finishes lane 0, all a selector needs. The last round skips rho, pi
+/// and chi for the other 24 lanes.
+///
+/// Chi is mostly and-not, so on x86-64 CPUs with BMI the permutation is compiled for it.
+#[inline]
+pub fn keccak_f1600_first_lane(state: &mut [u64; 25]) -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("bmi1") && std::arch::is_x86_feature_detected!("bmi2") {
+        // SAFETY: the CPU has the features the function is compiled for
+        return unsafe { first_lane_bmi(state) };
+    }
+    first_lane(state)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi1,bmi2")]
+fn first_lane_bmi(state: &mut [u64; 25]) -> u64 {
+    first_lane(state)
+}
+
+#[inline(always)]
+fn first_lane(state: &mut [u64; 25]) -> u64 {
+    let mut e = [0u64; 25];
+    for pair in ROUND_CONSTANTS[..22].chunks_exact(2) {
+        round(state, &mut e, pair[0]);
+        round(&e, state, pair[1]);
+    }
+    round(state, &mut e, ROUND_CONSTANTS[22]);
+
+    let d = theta(&e);
+    let [(_, _), (lane1, rotation1), (lane2, rotation2), ..] = ROWS[0];
+    let b1 = (e[lane1] ^ d[1]).rotate_left(rotation1);
+    let b2 = (e[lane2] ^ d[2]).rotate_left(rotation2);
+    (e[0] ^ d[0]) ^ (!b1 & b2) ^ ROUND_CONSTANTS[23]
+}
+
+/// Multi-buffer permutations, lane `i` of each of several states packed into one vector
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use std::arch::x86_64::*;
+
+    use super::{ROUND_CONSTANTS, ROWS};
+
+    /// One lane of several states, every Keccak step is one vector operation
+    trait Lanes: Copy {
+        fn splat(word: u64) -> Self;
+        fn xor(self, other: Self) -> Self;
+        /// `!self & other`
+        fn and_not(self, other: Self) -> Self;
+        fn rotate_left(self, n: u32) -> Self;
+    }
+
+    // SAFETY for both impls: only reached through the `target_feature` functions below, which
+    // callers only run on CPUs that have the features
+    impl Lanes for __m256i {
+        #[inline(always)]
+        fn splat(word: u64) -> Self {
+            unsafe { _mm256_set1_epi64x(word as i64) }
+        }
+
+        #[inline(always)]
+        fn xor(self, other: Self) -> Self {
+            unsafe { _mm256_xor_si256(self, other) }
+        }
+
+        #[inline(always)]
+        fn and_not(self, other: Self) -> Self {
+            unsafe { _mm256_andnot_si256(self, other) }
+        }
+
+        #[inline(always)]
+        fn rotate_left(self, n: u32) -> Self {
+            // AVX2 has no 64-bit rotate
+            unsafe {
+                let left = _mm256_sll_epi64(self, _mm_cvtsi32_si128(n as i32));
+                let right = _mm256_srl_epi64(self, _mm_cvtsi32_si128(64 - n as i32));
+                _mm256_or_si256(left, right)
+            }
+        }
+    }
+
+    impl Lanes for __m512i {
+        #[inline(always)]
+        fn splat(word: u64) -> Self {
+            unsafe { _mm512_set1_epi64(word as i64) }
+        }
+
+        #[inline(always)]
+        fn xor(self, other: Self) -> Self {
+            unsafe { _mm512_xor_si512(self, other) }
+        }
+
+        #[inline(always)]
+        fn and_not(self, other: Self) -> Self {
+            unsafe { _mm512_andnot_si512(self, other) }
+        }
+
+        #[inline(always)]
+        fn rotate_left(self, n: u32) -> Self {
+            unsafe { _mm512_rolv_epi64(self, _mm512_set1_epi64(n as i64)) }
+        }
+    }
+
+    // No closures below: `array::from_fn` and `map` are not inlined into the `target_feature`
+    // callers, which turns every vector operation into a call
+
+    #[inline(always)]
+    fn theta<V: Lanes>(a: &[V; 25]) -> [V; 5] {
+        let mut c = [a[0]; 5];
+        for x in 0..5 {
+            c[x] = a[x].xor(a[x + 5]).xor(a[x + 10]).xor(a[x + 15]).xor(a[x + 20]);
+        }
+        let mut d = c;
+        for x in 0..5 {
+            d[x] = c[(x + 4) % 5].xor(c[(x + 1) % 5].rotate_left(1));
+        }
+        d
+    }
+
+    #[inline(always)]
+    fn round<V: Lanes>(a: &[V; 25], e: &mut [V; 25], round_constant: u64) {
+        let d = theta(a);
+        for (y, row) in ROWS.iter().enumerate() {
+            let mut b = [a[0]; 5];
+            for (x, &(lane, rotation)) in row.iter().enumerate() {
+                b[x] = a[lane].xor(d[lane % 5]).rotate_left(rotation);
+            }
+            for x in 0..5 {
+                e[5 * y + x] = b[x].xor(b[(x + 1) % 5].and_not(b[(x + 2) % 5]));
+            }
+        }
+        e[0] = e[0].xor(V::splat(round_constant));
+    }
+
+    /// [`super::keccak_f1600_first_lane`] on every state packed into `state`
+    #[inline(always)]
+    fn first_lane<V: Lanes>(state: &mut [V; 25]) -> V {
+        let mut e = *state;
+        for pair in ROUND_CONSTANTS[..22].chunks_exact(2) {
+            round(state, &mut e, pair[0]);
+            round(&e, state, pair[1]);
+        }
+        round(state, &mut e, ROUND_CONSTANTS[22]);
+
+        let d = theta(&e);
+        let b1 = e[6].xor(d[1]).rotate_left(44);
+        let b2 = e[12].xor(d[2]).rotate_left(43);
+        e[0].xor(d[0]).xor(b1.and_not(b2)).xor(V::splat(ROUND_CONSTANTS[23]))
+    }
+
+    /// Lane 0 of the output of 4 states, given their lanes
+    #[target_feature(enable = "avx2")]
+    pub fn first_lane_x4(state: &[[u64; 4]; 25]) -> [u64; 4] {
+        // SAFETY: `[u64; 4]` and `__m256i` have the same size, accesses are unaligned
+        unsafe {
+            let mut vectors = [_mm256_setzero_si256(); 25];
+            for (vector, words) in vectors.iter_mut().zip(state) {
+                *vector = _mm256_loadu_si256(words.as_ptr().cast());
+            }
+            let output = first_lane(&mut vectors);
+            let mut words = [0u64; 4];
+            _mm256_storeu_si256(words.as_mut_ptr().cast(), output);
+            words
+        }
+    }
+
+    /// Lane 0 of the output of 8 states, given their lanes
+    #[target_feature(enable = "avx512f")]
+    pub fn first_lane_x8(state: &[[u64; 8]; 25]) -> [u64; 8] {
+        // SAFETY: `[u64; 8]` and `__m512i` have the same size, accesses are unaligned
+        unsafe {
+            let mut vectors = [_mm512_setzero_si512(); 25];
+            for (vector, words) in vectors.iter_mut().zip(state) {
+                *vector = _mm512_loadu_si512(words.as_ptr().cast());
+            }
+            let output = first_lane(&mut vectors);
+            let mut words = [0u64; 8];
+            _mm512_storeu_si512(words.as_mut_ptr().cast(), output);
+            words
+        }
+    }
+}
+
+/// Most candidates any kernel hashes per permutation
+pub const MAX_LANES: usize = 8;
+
+/// Keccak-f[1600] implementations a [`SelectorHasher`] can run on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    /// One candidate per permutation
+    Scalar,
+    /// Four candidates per permutation in 256-bit vectors
+    Avx2,
+    /// Eight candidates per permutation in 512-bit vectors
+    Avx512,
+}
+
+impl Kernel {
+    /// Widest kernel the CPU supports
+    pub fn detect() -> Self {
+        [Kernel::Avx512, Kernel::Avx2]
+            .into_iter()
+            .find(|kernel| kernel.is_supported())
+            .unwrap_or(Kernel::Scalar)
+    }
+
+    /// Whether the CPU can run this kernel
+    pub fn is_supported(self) -> bool {
+        match self {
+            Kernel::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => std::arch::is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx512 => std::arch::is_x86_feature_detected!("avx512f"),
+            #[cfg(not(target_arch = "x86_64"))]
+            Kernel::Avx2 | Kernel::Avx512 => false,
+        }
+    }
+
+    /// Candidates hashed per permutation
+    pub fn lanes(self) -> usize {
+        match self {
+            Kernel::Scalar => 1,
+            Kernel::Avx2 => 4,
+            Kernel::Avx512 => 8,
+        }
+    }
+}
+
+impl fmt::Display for Kernel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kernel::Scalar => write!(f, "scalar"),
+            Kernel::Avx2 => write!(f, "AVX2 4-way"),
+            Kernel::Avx512 => write!(f, "AVX-512 8-way"),
+        }
+    }
+}
+
+/// Selector from lane 0 of the output, its first 4 bytes read big-endian
+#[inline]
+fn lane_selector(lane: u64) -> u32 {
+    (lane as u32).swap_bytes()
+}
+
+/// Final block of an input, followed by zeros up to the rate and without padding
+#[derive(Debug, Clone)]
+struct Block {
+    bytes: [u8; RATE],
+    len: usize,
+    /// Whether `bytes` holds the fixed prefix yet
+    loaded: bool,
+}
+
+impl Default for Block {
+    fn default() -> Self {
+        Self { bytes: [0; RATE], len: 0, loaded: false }
+    }
+}
+
+impl Block {
+    /// Copies `input` over the last one, skipping the `fixed` bytes they share
+    #[inline]
+    fn load(&mut self, input: &[u8], fixed: usize) {
+        let first = if self.loaded { fixed.min(input.len()) } else { 0 };
+        self.loaded = true;
+        self.bytes[first..input.len()].copy_from_slice(&input[first..]);
+        if input.len() < self.len {
+            self.bytes[input.len()..self.len].fill(0);
+        }
+        self.len = input.len();
+    }
+
+    /// Rate lanes with the Keccak padding, 0x01 after the input and 0x80 in the last byte
+    #[inline]
+    fn lanes(&self) -> [u64; RATE_LANES] {
+        let mut lanes: [u64; RATE_LANES] = std::array::from_fn(|lane| {
+            u64::from_le_bytes(self.bytes[lane * 8..lane * 8 + 8].try_into().unwrap())
+        });
+        lanes[self.len / 8] ^= 0x01 << (8 * (self.len % 8));
+        lanes[RATE_LANES - 1] ^= 0x80 << 56;
+        lanes
+    }
+}
+
+/// Xors a whole block into `state` and permutes it
+fn absorb(state: &mut [u64; 25], block: &[u8]) {
+    for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+        *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+    keccak_f1600(state);
+}
+
+/// Hashes candidates and returns their selectors.
+///
+/// The whole blocks of the `fixed` bytes every input starts with are absorbed once, from the
+/// first input, and each input starts from a copy of that midstate. Only the rest of the input,
+/// which holds the varying part, is absorbed per candidate. When that rest is shorter than
+/// [`RATE`] it is the final padded block, which is built directly and permuted without the
+/// digest bookkeeping, only lane 0 of the output is computed. The fixed bytes in that block are
+/// only copied into it once.
+///
+/// [`SelectorHasher::selectors`] hashes as many inputs at once as the [`Kernel`] has lanes.
+#[derive(Debug, Clone)]
+pub struct SelectorHasher {
+    /// Final block of the last input hashed in each lane
+    blocks: [Block; MAX_LANES],
+    /// Leading bytes every input shares
+    fixed: usize,
+    /// Leading bytes in the midstate, the whole blocks of the fixed ones
+    absorbed: usize,
+    /// State after absorbing them, `None` until the first input
+    midstate: Option<[u64; 25]>,
+    kernel: Kernel,
+}
+
+impl SelectorHasher {
+    /// Hasher on the widest kernel the CPU supports for inputs that all start with the same
+    /// `fixed` bytes
+    pub fn new(fixed: usize) -> Self {
+        Self::with_kernel(fixed, Kernel::detect())
+    }
+
+    /// Hasher on a given kernel, which must be supported
+    pub fn with_kernel(fixed: usize, kernel: Kernel) -> Self {
+        assert!(kernel.is_supported(), "{} is not supported on this CPU", kernel);
+        let absorbed = fixed / RATE * RATE;
+        Self {
+            blocks: std::array::from_fn(|_| Block::default()),
+            fixed,
+            absorbed,
+            midstate: (absorbed == 0).then_some([0; 25]),
+            kernel,
+        }
+    }
+
+    pub fn kernel(&self) -> Kernel {
+        self.kernel
+    }
+
+    /// Inputs [`SelectorHasher::selectors`] hashes at once
+    pub fn lanes(&self) -> usize {
+        self.kernel.lanes()
+    }
+
+    /// Leading bytes absorbed once instead of for every input
+    pub fn absorbed(&self) -> usize {
+        self.absorbed
+    }
+
+    /// State after the fixed whole blocks, absorbed from `input` the first time
+    #[inline]
+    fn midstate(&mut self, input: &[u8]) -> [u64; 25] {
+        *self.midstate.get_or_insert_with(|| {
+            let mut state = [0; 25];
+            for block in input[..self.absorbed].chunks_exact(RATE) {
+                absorb(&mut state, block);
+            }
+            state
+        })
+    }
+
+    /// Selector of `input`, which must start with the fixed bytes given to
+    /// [`SelectorHasher::new`]
+    #[inline]
+    pub fn selector(&mut self, input: &[u8]) -> u32 {
+        let mut state = self.midstate(input);
+        let rest = &input[self.absorbed..];
+        let block = if rest.len() < RATE {
+            self.blocks[0].load(rest, self.fixed - self.absorbed);
+            self.blocks[0].lanes()
+        } else {
+            // The varying part spans several blocks, only the fixed ones before it are shared
+            let whole = rest.len() / RATE * RATE;
+            for block in rest[..whole].chunks_exact(RATE) {
+                absorb(&mut state, block);
+            }
+            let mut last = Block::default();
+            last.load(&rest[whole..], 0);
+            last.lanes()
+        };
+        for (lane, word) in state.iter_mut().zip(block) {
+            *lane ^= word;
+        }
+        lane_selector(keccak_f1600_first_lane(&mut state))
+    }
+
+    /// Selectors of up to [`SelectorHasher::lanes`] inputs, hashed together
+    #[inline]
+    pub fn selectors<T: AsRef<[u8]>>(&mut self, inputs: &[T]) -> [u32; MAX_LANES] {
+        assert!(inputs.len() <= self.lanes(), "more inputs than lanes");
+        let mut selectors = [0u32; MAX_LANES];
+        let single_block = |input: &T| input.as_ref().len() - self.absorbed < RATE;
+        if self.kernel == Kernel::Scalar || !inputs.iter().all(single_block) {
+            for (selector, input) in selectors.iter_mut().zip(inputs) {
+                *selector = self.selector(input.as_ref());
+            }
+            return selectors;
+        }
+        let Some(first) = inputs.first() else {
+            return selectors;
+        };
+
+        let midstate = self.midstate(first.as_ref());
+        for (block, input) in self.blocks.iter_mut().zip(inputs) {
+            block.load(&input.as_ref()[self.absorbed..], self.fixed - self.absorbed);
+        }
+        // Lanes past the inputs hash whatever they held before and are ignored
+        let lanes: [[u64; RATE_LANES]; MAX_LANES] = std::array::from_fn(|i| self.blocks[i].lanes());
+        match self.kernel {
+            Kernel::Scalar => unreachable!(),
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => {
+                let mut state = midstate.map(|word| [word; 4]);
+                for (lane, words) in state.iter_mut().take(RATE_LANES).enumerate() {
+                    for (i, word) in words.iter_mut().enumerate() {
+                        *word ^= lanes[i][lane];
+                    }
+                }
+                // SAFETY: `with_kernel` checked that the CPU has AVX2
+                let output = unsafe { simd::first_lane_x4(&state) };
+                for (selector, lane) in selectors.iter_mut().zip(output) {
+                    *selector = lane_selector(lane);
+                }
+            }
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx512 => {
+                let mut state = midstate.map(|word| [word; 8]);
+                for (lane, words) in state.iter_mut().take(RATE_LANES).enumerate() {
+                    for (i, word) in words.iter_mut().enumerate() {
+                        *word ^= lanes[i][lane];
+                    }
+                }
+                // SAFETY: `with_kernel` checked that the CPU has AVX-512
+                let output = unsafe { simd::first_lane_x8(&state) };
+                for (selector, lane) in selectors.iter_mut().zip(output) {
+                    *selector = lane_selector(lane);
+                }
+            }
+            #[cfg(not(target_arch = "x86_64"))]
+            Kernel::Avx2 | Kernel::Avx512 => unreachable!(),
+        }
+        selectors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kernel, MAX_LANES, RATE, SelectorHasher, keccak_f1600, keccak_f1600_first_lane};
+    use crate::{calculate_keccak_256, hash_selector};
+
+    fn selector(input: &[u8]) -> u32 {
+        hash_selector(&calculate_keccak_256(input))
+    }
+
+    #[test]
+    fn test_permutation() {
+        // Lane 0 of Keccak-f[1600] applied to the zero state
+        let mut state = [0u64; 25];
+        keccak_f1600(&mut state);
+        assert_eq!(state[0], 0xf1258f7940e1dde7);
+        assert_eq!(state[24], 0xeaf1ff7b5ceca249);
+
+        let mut state = [0u64; 25];
+        assert_eq!(keccak_f1600_first_lane(&mut state), 0xf1258f7940e1dde7);
+    }
+
+    #[test]
+    fn test_single_block() {
+        let mut hasher = SelectorHasher::new(0);
+        assert_eq!(hasher.selector(b"transfer(address,uint256)"), 0xa9059cbb);
+        assert_eq!(hasher.selector(b""), selector(b""));
+
+        // Every length up to and past one block, including the padding sharing a byte
+        let input: Vec<u8> = (0..300u32).map(|i| (i * 7 + 3) as u8).collect();
+        for len in 0..input.len() {
+            assert_eq!(hasher.selector(&input[..len]), selector(&input[..len]), "{}", len);
+        }
+        assert_eq!(hasher.selector(&input[..RATE - 1]), selector(&input[..RATE - 1]));
+    }
+
+    #[test]
+    fn test_fixed_prefix() {
+        let prefix = "aVeryLongFunctionNamePrefix_";
+        let mut hasher = SelectorHasher::new(prefix.len());
+        for i in (0..100_000).step_by(997) {
+            let input = format!("{}{}(address,uint256)", prefix, i);
+            assert_eq!(hasher.selector(input.as_bytes()), selector(input.as_bytes()), "{}", input);
+        }
+        // The prefix alone fills most of a block
+        let prefix = "x".repeat(130);
+        let mut hasher = SelectorHasher::new(prefix.len());
+        for suffix in ["", "1", "12345", "123456"] {
+            let input = format!("{}{}", prefix, suffix);
+            assert_eq!(hasher.selector(input.as_bytes()), selector(input.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_midstate() {
+        // Prefixes short of, at and past whole blocks, with the rest ending on either side of a
+        // block boundary
+        for fixed in [RATE - 1, RATE, RATE + 5, 2 * RATE + 40] {
+            let prefix: String = (0..fixed).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+            let kernels = [Kernel::Scalar, Kernel::Avx2, Kernel::Avx512];
+            for kernel in kernels.into_iter().filter(|kernel| kernel.is_supported()) {
+                let mut hasher = SelectorHasher::with_kernel(fixed, kernel);
+                assert_eq!(hasher.absorbed(), fixed / RATE * RATE);
+                let inputs: Vec<String> = (0..200)
+                    .map(|i| format!("{}{}({})", prefix, i * 7919, "uint256,".repeat(i % 40)))
+                    .collect();
+                for batch in inputs.chunks(hasher.lanes()) {
+                    let selectors = hasher.selectors(batch);
+                    for (input, batched) in batch.iter().zip(selectors) {
+                        assert_eq!(batched, selector(input.as_bytes()), "{} {}", kernel, input);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernels() {
+        let kernels = [Kernel::Scalar, Kernel::Avx2, Kernel::Avx512];
+        for kernel in kernels.into_iter().filter(|kernel| kernel.is_supported()) {
+            let prefix = "swapExactTokens_";
+            let mut hasher = SelectorHasher::with_kernel(prefix.len(), kernel);
+            let lanes = hasher.lanes();
+            assert!(lanes <= MAX_LANES);
+
+            // Lengths differ within a batch and cross into a second block
+            let inputs: Vec<String> = (0..500)
+                .map(|i| format!("{}{}({})", prefix, i * 37, "uint256,".repeat(i % 20)))
+                .collect();
+            for batch in inputs.chunks(lanes) {
+                let selectors = hasher.selectors(batch);
+                for (input, batched) in batch.iter().zip(selectors) {
+                    assert_eq!(batched, selector(input.as_bytes()), "{}", kernel);
+                }
+            }
+        }
+        assert!(Kernel::detect().is_supported());
+    }
+}
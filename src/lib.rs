@@ -1,10 +1,37 @@
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::{
+    ops::Range,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering},
+    },
+    time::Instant,
+};
 
-use keccak_asm::Digest;
-use log::warn;
+use log::{debug, info, warn};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
+use crate::{
+    backend::Backend,
+    candidate::{CandidateGenerator, Candidates},
+    deny_list::DenyList,
+    score::{Score, Scored},
+    target::Target,
+};
+
+pub mod alphabet;
+pub mod backend;
+pub mod candidate;
 pub mod cli;
+pub mod deny_list;
+pub mod expr;
+pub mod identifier;
+pub mod keccak;
+pub mod pattern;
+pub mod score;
+pub mod selector_set;
+pub mod style;
+pub mod target;
+pub mod wordlist;
 
 pub const HEX_LOOKUP_TABLE: [u8; 256] = {
     let mut table = [0xFFu8; 256]; // Default all values to 0xFF (invalid)
@@ -23,171 +50,342 @@ pub const HEX_LOOKUP_TABLE: [u8; 256] = {
 
 pub static HASH_COUNTER: AtomicU64 = AtomicU64::new(0);
 
+/// Keccak-256 of `input` with the first [`Backend`] compiled in, `keccak-asm` if it is
 #[inline]
 pub fn calculate_keccak_256(input: &[u8]) -> [u8; 32] {
-    let mut hasher = keccak_asm::Keccak256::new();
-    hasher.update(input);
-    let output: [u8; 32] = hasher.finalize().into();
-    output
+    Backend::ALL[0].keccak_256(input)
 }
 
+/// First 4 bytes of the hash as a big-endian `u32`
 #[inline]
-fn compare_hash(hash: [u8; 32], pattern: &[u8]) -> bool {
-    // Pattern is already validated so we dont check for != 0xFF
-    match pattern.len() {
-        0 => {
-            // Empty pattern matches everything
-            true
-        }
-        1 => {
-            // Single hex character (high nibble of first byte)
-            (hash[0] >> 4) == HEX_LOOKUP_TABLE[pattern[0] as usize]
-        }
-        2 => {
-            // First byte only
-            (hash[0] >> 4) == HEX_LOOKUP_TABLE[pattern[0] as usize]
-                && (hash[0] & 0x0F) == HEX_LOOKUP_TABLE[pattern[1] as usize]
-        }
-        3 => {
-            // First byte + high nibble of second
-            (hash[0] >> 4) == HEX_LOOKUP_TABLE[pattern[0] as usize]
-                && (hash[0] & 0x0F) == HEX_LOOKUP_TABLE[pattern[1] as usize]
-                && (hash[1] >> 4) == HEX_LOOKUP_TABLE[pattern[2] as usize]
-        }
-        4 => {
-            // First two bytes
-            (hash[0] >> 4) == HEX_LOOKUP_TABLE[pattern[0] as usize]
-                && (hash[0] & 0x0F) == HEX_LOOKUP_TABLE[pattern[1] as usize]
-                && (hash[1] >> 4) == HEX_LOOKUP_TABLE[pattern[2] as usize]
-                && (hash[1] & 0x0F) == HEX_LOOKUP_TABLE[pattern[3] as usize]
-        }
-        5 => {
-            // Two bytes + high nibble of third
-            (hash[0] >> 4) == HEX_LOOKUP_TABLE[pattern[0] as usize]
-                && (hash[0] & 0x0F) == HEX_LOOKUP_TABLE[pattern[1] as usize]
-                && (hash[1] >> 4) == HEX_LOOKUP_TABLE[pattern[2] as usize]
-                && (hash[1] & 0x0F) == HEX_LOOKUP_TABLE[pattern[3] as usize]
-                && (hash[2] >> 4) == HEX_LOOKUP_TABLE[pattern[4] as usize]
-        }
-        6 => {
-            // Three bytes
-            (hash[0] >> 4) == HEX_LOOKUP_TABLE[pattern[0] as usize]
-                && (hash[0] & 0x0F) == HEX_LOOKUP_TABLE[pattern[1] as usize]
-                && (hash[1] >> 4) == HEX_LOOKUP_TABLE[pattern[2] as usize]
-                && (hash[1] & 0x0F) == HEX_LOOKUP_TABLE[pattern[3] as usize]
-                && (hash[2] >> 4) == HEX_LOOKUP_TABLE[pattern[4] as usize]
-                && (hash[2] & 0x0F) == HEX_LOOKUP_TABLE[pattern[5] as usize]
-        }
-        7 => {
-            // Three bytes + high nibble of fourth
-            (hash[0] >> 4) == HEX_LOOKUP_TABLE[pattern[0] as usize]
-                && (hash[0] & 0x0F) == HEX_LOOKUP_TABLE[pattern[1] as usize]
-                && (hash[1] >> 4) == HEX_LOOKUP_TABLE[pattern[2] as usize]
-                && (hash[1] & 0x0F) == HEX_LOOKUP_TABLE[pattern[3] as usize]
-                && (hash[2] >> 4) == HEX_LOOKUP_TABLE[pattern[4] as usize]
-                && (hash[2] & 0x0F) == HEX_LOOKUP_TABLE[pattern[5] as usize]
-                && (hash[3] >> 4) == HEX_LOOKUP_TABLE[pattern[6] as usize]
+pub fn hash_selector(hash: &[u8; 32]) -> u32 {
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
+#[inline]
+fn compare_hash(hash: [u8; 32], target: &Target) -> bool {
+    // Target is parsed once before the search, so this is a single masked or range compare
+    target.matches(hash_selector(&hash))
+}
+
+// Use thread-local buffer to avoid allocations
+thread_local! {
+    static THREAD_BUFFER: std::cell::RefCell<Vec<u8>> = std::cell::RefCell::new(Vec::with_capacity(0));
+}
+
+/// Writes candidate `num` into the thread-local buffer and passes it to `f`
+#[inline]
+fn with_candidate<G: CandidateGenerator + ?Sized, R>(
+    candidates: &G,
+    num: u64,
+    f: impl FnOnce(&[u8]) -> R,
+) -> R {
+    THREAD_BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        candidates.write(num, &mut buffer);
+        f(&buffer)
+    })
+}
+
+/// Searches `range_start..end` of `candidates` in parallel on `backend` and returns the index of
+/// any signature matching `target` that is not on the deny list and passes
+/// [`CandidateGenerator::check`]
+pub fn generate_vanity_function_name<G: CandidateGenerator + ?Sized>(
+    target: &Target,
+    deny_list: &DenyList,
+    candidates: &G,
+    range_start: u64,
+    end: Option<u64>,
+    backend: Backend,
+) -> Option<u64> {
+    let range_end = candidates.range_end(end);
+
+    find_in_chunks(candidates, range_start..range_end, backend, |_, buffer, selector| {
+        // Only pattern hits are checked against the deny list, keeping the miss path short
+        if target.matches(selector) && !deny_list.denies(selector) {
+            if let Err(e) = candidates.check(buffer) {
+                debug!("Skipping {}: {}", String::from_utf8_lossy(buffer), e);
+                return false;
+            }
+            let function_name_hash = backend.keccak_256(buffer);
+
+            // Verify result
+            if hash_selector(&function_name_hash) == selector {
+                true
+            } else {
+                warn!("Result did not pass verification!");
+                warn!("Candidate {:08x} does not match {:?}", selector, &function_name_hash[..4]);
+                false
+            }
+        } else {
+            false
         }
-        8 => {
-            // Four bytes (most common case)
-            (hash[0] >> 4) == HEX_LOOKUP_TABLE[pattern[0] as usize]
-                && (hash[0] & 0x0F) == HEX_LOOKUP_TABLE[pattern[1] as usize]
-                && (hash[1] >> 4) == HEX_LOOKUP_TABLE[pattern[2] as usize]
-                && (hash[1] & 0x0F) == HEX_LOOKUP_TABLE[pattern[3] as usize]
-                && (hash[2] >> 4) == HEX_LOOKUP_TABLE[pattern[4] as usize]
-                && (hash[2] & 0x0F) == HEX_LOOKUP_TABLE[pattern[5] as usize]
-                && (hash[3] >> 4) == HEX_LOOKUP_TABLE[pattern[6] as usize]
-                && (hash[3] & 0x0F) == HEX_LOOKUP_TABLE[pattern[7] as usize]
+    })
+}
+
+/// Candidates each worker walks in order before it takes the next chunk
+const CHUNK_SIZE: u64 = 1 << 16;
+
+/// Splits `range` into chunks for the workers and returns any index `matches` accepts, given the
+/// index, the signature and its selector.
+///
+/// A worker writes the first signature of its chunk once and then only
+/// [`CandidateGenerator::advance`]s its own buffer, so most candidates cost a few changed bytes
+/// and no borrow of a thread local. Selectors come from the [`backend::Hasher`] of `backend`,
+/// which keeps the [`CandidateGenerator::fixed_prefix`] absorbed, in batches of consecutive
+/// candidates, as many as it takes at once. Workers stop early once a match is found elsewhere.
+fn find_in_chunks<G: CandidateGenerator + ?Sized>(
+    candidates: &G,
+    range: Range<u64>,
+    backend: Backend,
+    matches: impl Fn(u64, &[u8], u32) -> bool + Sync,
+) -> Option<u64> {
+    let found = AtomicBool::new(false);
+    let chunks = range.end.saturating_sub(range.start).div_ceil(CHUNK_SIZE);
+
+    (0..chunks).into_par_iter().find_map_any(|chunk| {
+        let start = range.start + chunk * CHUNK_SIZE;
+        let end = start.saturating_add(CHUNK_SIZE).min(range.end);
+        let mut hasher = backend.hasher(candidates.fixed_prefix());
+        let lanes = hasher.lanes();
+        let mut buffers = vec![Vec::with_capacity(256); lanes];
+        candidates.write(start, &mut buffers[0]);
+
+        let mut num = start;
+        let solution = 'chunk: loop {
+            // One candidate per kernel lane, each advanced from the one before
+            let batch = (end - num).min(lanes as u64) as usize;
+            for lane in 1..batch {
+                let (previous, next) = buffers.split_at_mut(lane);
+                next[0].clone_from(&previous[lane - 1]);
+                candidates.advance(num + lane as u64, &mut next[0]);
+            }
+            let selectors = hasher.selectors(&buffers[..batch]);
+            for (lane, (buffer, &selector)) in buffers.iter().zip(&selectors[..batch]).enumerate() {
+                if matches(num + lane as u64, buffer, selector) {
+                    break 'chunk Some(num + lane as u64);
+                }
+            }
+            num += batch as u64;
+            // Checking for other matches is not free, do it once per 4k candidates
+            if num == end
+                || (num >> 12 != (num - batch as u64) >> 12 && found.load(Ordering::Relaxed))
+            {
+                break None;
+            }
+            buffers.swap(0, batch - 1);
+            candidates.advance(num, &mut buffers[0]);
+        };
+
+        // One update per chunk keeps the counter exact without contention
+        let hashed = solution.map_or(num, |index| index + 1) - start;
+        HASH_COUNTER.fetch_add(hashed, Ordering::Relaxed);
+        if solution.is_some() {
+            found.store(true, Ordering::Relaxed);
         }
-        _ => {
-            panic!("Pattern longer than 4 bytes! This should never happen!");
+        solution
+    })
+}
+
+/// Outcome of [`generate_shortest_vanity_function_name`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shortest {
+    /// Index of the match, `None` if every length was searched without one
+    pub index: Option<u64>,
+    /// Identifier length of the match, or of the longest candidates searched
+    pub length: usize,
+    /// Number of shorter lengths searched completely without a match
+    pub exhausted: usize,
+}
+
+/// Searches candidates by increasing identifier length, the name without parameters, and returns
+/// a match of the shortest length that has one.
+///
+/// Every length is searched completely, across all bases and threads, before the next one, so
+/// no shorter candidate matches. `None` for word suffixes, whose lengths do not follow the index.
+pub fn generate_shortest_vanity_function_name(
+    target: &Target,
+    deny_list: &DenyList,
+    candidates: &Candidates,
+    backend: Backend,
+) -> Option<Shortest> {
+    let suffix_lengths = candidates.suffix().lengths()?;
+    let bases = candidates.bases();
+
+    let mut lengths: Vec<usize> = (0..bases)
+        .flat_map(|base| suffix_lengths.iter().map(move |(len, _)| candidates.base_len(base) + len))
+        .collect();
+    lengths.sort_unstable();
+    lengths.dedup();
+    if let Some(max) = candidates.max_len() {
+        lengths.retain(|&length| length <= max);
+    }
+
+    let mut exhausted = 0;
+    for &length in &lengths {
+        for base in 0..bases {
+            let Some(suffix_len) = length.checked_sub(candidates.base_len(base)) else {
+                continue;
+            };
+            let Some((_, range)) = suffix_lengths.iter().find(|(len, _)| *len == suffix_len) else {
+                continue;
+            };
+
+            // Suffix `n` of this base is candidate `n * bases + base`, keep that within a u64
+            let end = range.end.min((u64::MAX - base as u64) / bases as u64);
+            let only = candidates.base_only(base);
+            if let Some(suffix) = generate_vanity_function_name(
+                target,
+                deny_list,
+                &only,
+                range.start,
+                Some(end),
+                backend,
+            ) {
+                let index = Some(suffix * bases as u64 + base as u64);
+                return Some(Shortest { index, length, exhausted });
+            }
         }
+        exhausted += 1;
     }
+
+    Some(Shortest { index: None, length: lengths.last().copied().unwrap_or_default(), exhausted })
 }
 
-pub fn generate_vanity_function_name(
-    pattern: &[u8],
-    name: &[u8],
-    parameters: &[u8],
+/// Searches `range_start..end` for one index that satisfies every member at once: the signature
+/// each generator writes for it matches that member's own target, is not on the deny list and
+/// passes [`CandidateGenerator::check`]. Used to give a family of signatures, e.g. overloads,
+/// one shared suffix.
+///
+/// Members are tried in order and a candidate is dropped at the first one that fails, so only
+/// the first member is hashed for most indices. Put the hardest target first.
+pub fn generate_joint_vanity_function_name<G: CandidateGenerator>(
+    members: &[(Target, G)],
+    deny_list: &DenyList,
     range_start: u64,
     end: Option<u64>,
+    backend: Backend,
 ) -> Option<u64> {
-    // Pre-allocate prefix and suffix buffers
-    // First is just the name
-    let prefix_buffer = name;
-    // Second is the parameters part
-    let mut suffix_buffer = Vec::with_capacity(parameters.len() + 2);
-    suffix_buffer.push(b'(');
-    suffix_buffer.extend_from_slice(parameters);
-    suffix_buffer.push(b')');
-
-    // Use thread-local buffer to avoid allocations
-    thread_local! {
-        static THREAD_BUFFER: std::cell::RefCell<Vec<u8>> = std::cell::RefCell::new(Vec::with_capacity(0));
-        static DIGIT_BUFFER: std::cell::RefCell<[u8; 20]> = const { std::cell::RefCell::new([0u8; 20]) };
-    }
+    let range_end = members.iter().map(|(_, candidates)| candidates.range_end(end)).min()?;
+
+    // The first member counts through the chunks, the rest are only written when it matches
+    let ((first_target, first), rest) = members.split_first()?;
+    find_in_chunks(first, range_start..range_end, backend, |num, buffer, selector| {
+        first_target.matches(selector)
+            && !deny_list.denies(selector)
+            && first.check(buffer).is_ok()
+            && rest.iter().all(|(target, candidates)| {
+                with_candidate(candidates, num, |buffer| {
+                    let hash = backend.keccak_256(buffer);
+                    compare_hash(hash, target)
+                        && !deny_list.denies(hash_selector(&hash))
+                        && candidates.check(buffer).is_ok()
+                })
+            })
+    })
+}
 
-    let range_end = end.unwrap_or(u64::MAX);
-
-    (range_start..range_end).into_par_iter().find_any(|&num| {
-        THREAD_BUFFER.with(|buffer| {
-            let mut buffer = buffer.borrow_mut();
-            buffer.clear();
-            buffer.extend_from_slice(prefix_buffer);
-
-            if num > 0 {
-                DIGIT_BUFFER.with(|digits| {
-                    let mut digits = digits.borrow_mut();
-                    let mut n = num;
-                    let mut pos = 20;
-
-                    // Convert directly to ASCII digits
-                    while n > 0 {
-                        pos -= 1;
-                        digits[pos] = b'0' + (n % 10) as u8;
-                        n /= 10;
-                    }
-
-                    buffer.extend_from_slice(&digits[pos..20]);
-                });
-            }
+/// Searches `range_start..end` until `deadline` or the candidates run out and returns the
+/// `top_k` best scoring candidates, best first, skipping selectors on the deny list.
+///
+/// Every worker thread keeps its own top list, merged once the search stops, so no two threads
+/// ever wait on the same lock. They share the best score and the highest floor of a full list
+/// through atomics, a candidate below it can not make the merged top list either.
+#[allow(clippy::too_many_arguments)]
+pub fn optimize_vanity_function_name<S: Score, G: CandidateGenerator + ?Sized>(
+    scorer: &S,
+    deny_list: &DenyList,
+    candidates: &G,
+    range_start: u64,
+    end: Option<u64>,
+    deadline: Instant,
+    top_k: usize,
+    backend: Backend,
+) -> Vec<Scored> {
+    let range_end = candidates.range_end(end);
+
+    let best = AtomicU32::new(0);
+    // Highest lowest score of a full worker list, -1 while every candidate still gets in
+    let floor = AtomicI64::new(-1);
+    // One list per worker, only ever locked by its own thread
+    let workers: Vec<Mutex<Vec<Scored>>> = (0..rayon::current_num_threads())
+        .map(|_| Mutex::new(Vec::with_capacity(top_k + 1)))
+        .collect();
+
+    // Running out of time is the only match, it stops every worker
+    find_in_chunks(candidates, range_start..range_end, backend, |num, buffer, selector| {
+        // Checking the clock is not free, do it once per 4k candidates
+        if (num & 0xFFF) == 0 && Instant::now() >= deadline {
+            return true;
+        }
 
-            buffer.extend_from_slice(&suffix_buffer);
+        let score = scorer.score(selector);
+        if top_k == 0
+            || i64::from(score) < floor.load(Ordering::Relaxed)
+            || deny_list.denies(selector)
+        {
+            return false;
+        }
+        if candidates.check(buffer).is_err() {
+            return false;
+        }
 
-            let hash = calculate_keccak_256(&buffer);
+        let worker = rayon::current_thread_index().unwrap_or(0) % workers.len();
+        let mut top = workers[worker].lock().unwrap();
+        if insert_scored(&mut top, Scored { index: num, selector, score }, top_k) {
+            floor.fetch_max(i64::from(top[top_k - 1].score), Ordering::Relaxed);
+        }
+        drop(top);
 
-            // Increment hash counter (less frequently to reduce atomic contention)
-            if (num & 0xFFFFF) == 0 {
-                HASH_COUNTER.fetch_add(1048575, Ordering::Relaxed);
-            }
+        if best.fetch_max(score, Ordering::Relaxed) < score {
+            info!("New best score {}: 0x{:08x} for {}", score, selector, candidates.render(num));
+        }
 
-            if compare_hash(hash, pattern) {
-                let function_name = std::str::from_utf8(&buffer).unwrap();
-                let function_name_hash = calculate_keccak_256(function_name.as_bytes());
-
-                // Verify result
-                if function_name_hash == hash {
-                    true
-                } else {
-                    warn!("Result did not pass verification!");
-                    warn!(
-                        "Candidate {:?} does not match {:?}",
-                        &hash[..4],
-                        &function_name_hash[..4]
-                    );
-                    false
-                }
-            } else {
-                false
-            }
-        })
-    })
+        false
+    });
+
+    let mut top = Vec::with_capacity(top_k + 1);
+    for scored in workers.into_iter().flat_map(|worker| worker.into_inner().unwrap()) {
+        insert_scored(&mut top, scored, top_k);
+    }
+    top
+}
+
+/// Inserts `scored` into a top list of at most `top_k`, best first, and returns whether the list
+/// is full afterwards.
+///
+/// Ties keep the lowest index so results do not depend on scheduling.
+fn insert_scored(top: &mut Vec<Scored>, scored: Scored, top_k: usize) -> bool {
+    let key = |scored: &Scored| (scored.score, std::cmp::Reverse(scored.index));
+    let position = top.iter().position(|other| key(&scored) > key(other)).unwrap_or(top.len());
+    if position < top_k {
+        top.insert(position, scored);
+        top.truncate(top_k);
+    }
+    top.len() == top_k
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, Instant};
+
     use crate::{
-        HEX_LOOKUP_TABLE, calculate_keccak_256, compare_hash, generate_vanity_function_name,
+        HEX_LOOKUP_TABLE, Shortest,
+        alphabet::Alphabet,
+        backend::Backend,
+        calculate_keccak_256,
+        candidate::{CandidateGenerator, Candidates, Suffix},
+        compare_hash,
+        deny_list::DenyList,
+        generate_joint_vanity_function_name, generate_shortest_vanity_function_name,
+        generate_vanity_function_name, hash_selector,
+        identifier::NameError,
+        keccak::{RATE, SelectorHasher},
+        optimize_vanity_function_name,
+        pattern::Pattern,
+        score::{LeadingZeroBits, Score, Smallness, ZeroBytes},
+        target::Target,
+        wordlist::Wordlists,
     };
 
     #[test]
@@ -208,24 +406,31 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x00,
         ];
+        let pattern = |p: &str| Target::from(Pattern::parse(p).unwrap());
 
-        assert!(compare_hash(hash, b"1")); // Just high nibble of first byte
-        assert!(compare_hash(hash, b"12")); // First byte
-        assert!(!compare_hash(hash, b"13")); // Should fail
+        assert!(compare_hash(hash, &pattern("1"))); // Just high nibble of first byte
+        assert!(compare_hash(hash, &pattern("12"))); // First byte
+        assert!(!compare_hash(hash, &pattern("13"))); // Should fail
 
         // Test complete 4-byte pattern
-        assert!(compare_hash(hash, b"12345678"));
+        assert!(compare_hash(hash, &pattern("12345678")));
+
+        // Wildcards only pin the remaining nibbles
+        assert!(compare_hash(hash, &pattern("12??56?8")));
+        assert!(compare_hash(hash, &pattern("??????78")));
+        assert!(!compare_hash(hash, &pattern("??????79")));
     }
 
     #[test]
     fn test_generate_vanity_function_name() {
         // Test with known solution
         let solution = generate_vanity_function_name(
-            b"1234",            // Pattern to match
-            b"transfer",        // Function name
-            b"address,uint256", // Parameters
-            0,                  // Start range
-            Some(1000000),      // End range (limit for faster test)
+            &Pattern::parse("1234").unwrap().into(), // Pattern to match
+            &DenyList::default(),                    // Nothing denied
+            &Candidates::decimal("transfer", "address,uint256").unwrap(), // Name and parameters
+            0,                                       // Start range
+            Some(1000000),                           // End range (limit for faster test)
+            Backend::default(),
         );
 
         assert!(solution.is_some());
@@ -238,15 +443,277 @@ mod tests {
         assert_eq!(format!("{:02x}{:02x}", hash[0], hash[1]), "1234");
     }
 
+    #[test]
+    fn test_identifier_alphabet_search() {
+        let candidates =
+            Candidates::new("transfer", "address,uint256", Alphabet::Identifier).unwrap();
+        let solution = generate_vanity_function_name(
+            &Pattern::parse("1234").unwrap().into(),
+            &DenyList::default(),
+            &candidates,
+            0,
+            Some(1000000),
+            Backend::default(),
+        );
+
+        // The suffix uses the full identifier set and is shorter than the decimal index
+        let solution_index = solution.unwrap();
+        let function_name = candidates.render(solution_index);
+        assert!(function_name.len() < format!("transfer{}(address,uint256)", solution_index).len());
+        let hash = calculate_keccak_256(function_name.as_bytes());
+        assert_eq!(format!("{:02x}{:02x}", hash[0], hash[1]), "1234");
+    }
+
+    #[test]
+    fn test_template_search() {
+        let candidates =
+            Candidates::new("v{}_transfer", "address,uint256", Alphabet::Base62).unwrap();
+        let solution = generate_vanity_function_name(
+            &Pattern::parse("1234").unwrap().into(),
+            &DenyList::default(),
+            &candidates,
+            0,
+            Some(1000000),
+            Backend::default(),
+        );
+
+        let function_name = candidates.render(solution.unwrap());
+        assert!(function_name.starts_with('v'));
+        assert!(function_name.ends_with("_transfer(address,uint256)"));
+        let hash = calculate_keccak_256(function_name.as_bytes());
+        assert_eq!(format!("{:02x}{:02x}", hash[0], hash[1]), "1234");
+    }
+
+    #[test]
+    fn test_fixed_width_search() {
+        let candidates =
+            Candidates::decimal("transfer_", "uint256").unwrap().fixed_width(2).unwrap();
+        let target: Target = Pattern::parse("00").unwrap().into();
+
+        // 100 candidates are too few for a 1 in 256 target, the search stops at the last one
+        let expected =
+            (0..100).find(|&n| calculate_keccak_256(candidates.render(n).as_bytes())[0] == 0);
+        let solution = generate_vanity_function_name(
+            &target,
+            &DenyList::default(),
+            &candidates,
+            0,
+            None,
+            Backend::default(),
+        );
+        assert_eq!(solution, expected);
+
+        let top = optimize_vanity_function_name(
+            &Smallness,
+            &DenyList::default(),
+            &candidates,
+            0,
+            None,
+            Instant::now() + Duration::from_secs(60),
+            200,
+            Backend::default(),
+        );
+        assert_eq!(top.len(), 100);
+    }
+
+    #[test]
+    fn test_shortest_search() {
+        let target: Target = Pattern::parse("12").unwrap().into();
+        let bases = [("transferTokens", "uint256"), ("send", "uint256")];
+        let suffix = Suffix::Numeral { alphabet: Alphabet::Base36, width: None };
+        let candidates = Candidates::from_bases(bases, suffix).unwrap();
+
+        let shortest = generate_shortest_vanity_function_name(
+            &target,
+            &DenyList::default(),
+            &candidates,
+            Backend::default(),
+        )
+        .unwrap();
+        let index = shortest.index.unwrap();
+        let name = candidates.render(index);
+        let hash = calculate_keccak_256(name.as_bytes());
+        assert_eq!(hash[0], 0x12);
+        assert_eq!(name.len() - "(uint256)".len(), shortest.length);
+        assert_eq!(shortest.exhausted, shortest.length - "send".len());
+
+        // No candidate up to 100000 is shorter than the one found
+        let brute_force = (0..100_000)
+            .map(|n| candidates.render(n))
+            .filter(|name| calculate_keccak_256(name.as_bytes())[0] == 0x12)
+            .map(|name| name.len() - "(uint256)".len())
+            .min();
+        assert_eq!(brute_force, Some(shortest.length));
+
+        let words = Wordlists::new(vec![vec!["get".into()]]).unwrap();
+        let candidates = Candidates::from_wordlists("pool", "", words).unwrap();
+        assert!(
+            generate_shortest_vanity_function_name(
+                &target,
+                &DenyList::default(),
+                &candidates,
+                Backend::default()
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_shortest_exhausted() {
+        // A single bare name, its own selector matches and anything else does not
+        let candidates = Candidates::from_bases([("mint", "")], Suffix::None).unwrap();
+        let selector = hash_selector(&calculate_keccak_256(b"mint()"));
+        let own: Target = Pattern::from_mask_value(u32::MAX, selector).unwrap().into();
+        let other = Target::Not(Box::new(own.clone()));
+
+        let shortest = generate_shortest_vanity_function_name(
+            &own,
+            &DenyList::default(),
+            &candidates,
+            Backend::default(),
+        );
+        assert_eq!(shortest, Some(Shortest { index: Some(0), length: 4, exhausted: 0 }));
+        let shortest = generate_shortest_vanity_function_name(
+            &other,
+            &DenyList::default(),
+            &candidates,
+            Backend::default(),
+        );
+        assert_eq!(shortest, Some(Shortest { index: None, length: 4, exhausted: 1 }));
+    }
+
+    #[test]
+    fn test_mask_value_search() {
+        // Lowest 5 bits equal 0b10110
+        let pattern = Pattern::from_mask_value(0x1F, 0b10110).unwrap();
+        let solution = generate_vanity_function_name(
+            &pattern.into(),
+            &DenyList::default(),
+            &Candidates::decimal("mint", "uint256").unwrap(),
+            0,
+            Some(10000),
+            Backend::default(),
+        );
+
+        let function_name = format!("mint{}(uint256)", solution.unwrap());
+        let hash = calculate_keccak_256(function_name.as_bytes());
+        assert_eq!(hash[3] & 0x1F, 0b10110);
+    }
+
+    #[test]
+    fn test_range_search() {
+        let target = Target::below(0x01000000).unwrap();
+        let solution = generate_vanity_function_name(
+            &target,
+            &DenyList::default(),
+            &Candidates::decimal("mint", "").unwrap(),
+            0,
+            Some(10000),
+            Backend::default(),
+        );
+
+        let function_name = format!("mint{}()", solution.unwrap());
+        let hash = calculate_keccak_256(function_name.as_bytes());
+        assert!(u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]) < 0x01000000);
+    }
+
+    #[test]
+    fn test_optimize_vanity_function_name() {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let top = optimize_vanity_function_name(
+            &LeadingZeroBits,
+            &DenyList::default(),
+            &Candidates::decimal("mint", "uint256").unwrap(),
+            0,
+            Some(5000),
+            deadline,
+            3,
+            Backend::default(),
+        );
+
+        // Same ranking as scoring every candidate by hand
+        let mut expected: Vec<(u32, u64)> = (0..5000u64)
+            .map(|n| {
+                let hash = calculate_keccak_256(format!("mint{}(uint256)", n).as_bytes());
+                (LeadingZeroBits.score(hash_selector(&hash)), n)
+            })
+            .collect();
+        expected.sort_by_key(|&(score, n)| (std::cmp::Reverse(score), n));
+
+        assert_eq!(top.len(), 3);
+        for (scored, (score, n)) in top.iter().zip(expected) {
+            assert_eq!((scored.score, scored.index), (score, n));
+        }
+    }
+
+    #[test]
+    fn test_optimize_ties_and_zero_scores() {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        // Fewer candidates than places, each of them is listed whatever it scores
+        let names = ["mint", "Mint", "mint_", "MINT"];
+        let candidates =
+            Candidates::from_bases(names.iter().map(|name| (*name, "")), Suffix::None).unwrap();
+        let top = optimize_vanity_function_name(
+            &ZeroBytes,
+            &DenyList::default(),
+            &candidates,
+            0,
+            None,
+            deadline,
+            10,
+            Backend::default(),
+        );
+        assert_eq!(top.len(), names.len());
+
+        // Most selectors tie on zero bytes, the lowest indices win the ties
+        let candidates = Candidates::decimal("mint", "uint256").unwrap();
+        let top = optimize_vanity_function_name(
+            &ZeroBytes,
+            &DenyList::default(),
+            &candidates,
+            0,
+            Some(100_000),
+            deadline,
+            20,
+            Backend::default(),
+        );
+        let mut expected: Vec<(u32, u64)> = (0..100_000u64)
+            .map(|n| {
+                let hash = calculate_keccak_256(candidates.render(n).as_bytes());
+                (ZeroBytes.score(hash_selector(&hash)), n)
+            })
+            .collect();
+        expected.sort_by_key(|&(score, n)| (std::cmp::Reverse(score), n));
+        let top: Vec<(u32, u64)> = top.iter().map(|scored| (scored.score, scored.index)).collect();
+        assert_eq!(top, expected[..20]);
+    }
+
+    #[test]
+    fn test_optimize_deadline() {
+        // An expired budget returns right away with whatever was seen
+        let top = optimize_vanity_function_name(
+            &Smallness,
+            &DenyList::default(),
+            &Candidates::decimal("mint", "").unwrap(),
+            0,
+            None,
+            Instant::now(),
+            5,
+            Backend::default(),
+        );
+        assert!(top.len() <= 5);
+    }
+
     #[test]
     fn test_empty_pattern() {
         // Empty pattern should match anything
         let solution = generate_vanity_function_name(
-            b"",     // Empty pattern
-            b"test", // Function name
-            b"",     // No parameters
+            &Pattern::default().into(), // Empty pattern
+            &DenyList::default(),       // Nothing denied
+            &Candidates::decimal("test", "").unwrap(),
             0,
             Some(10), // Should find solution quickly
+            Backend::default(),
         );
 
         assert!(solution.is_some());
@@ -255,21 +722,129 @@ mod tests {
 
     #[test]
     fn test_empty_function_name() {
-        // Not a valid Solidity function, but should still hash
+        // Only a number would be left of the name, which is not a Solidity identifier
+        assert_eq!(Candidates::decimal("", "").unwrap_err(), NameError::Empty);
+        assert_eq!(Candidates::decimal("1st", "").unwrap_err(), NameError::InvalidStart('1'));
+    }
+
+    #[test]
+    fn test_invalid_names_skipped() {
+        // `uint` + `8` is a type, `uint` + `0` is fine and the decimal numbering never leads with 0
+        let candidates = Candidates::decimal("uint", "").unwrap();
+        let names: Vec<String> = (0..300).map(|i| candidates.render(i)).collect();
+        let checked = |name: &String| candidates.check(name.as_bytes());
+        assert_eq!(checked(&names[0]), Err(NameError::Reserved("uint".to_string())));
+        assert_eq!(checked(&names[8]), Err(NameError::Reserved("uint8".to_string())));
+        assert_eq!(checked(&names[9]), Ok(()));
+
+        // Every selector of `uint256()` is denied unless the search skips the reserved name
+        let selector = hash_selector(&calculate_keccak_256(b"uint256()"));
+        let target = Target::from(Pattern::parse(&format!("{:08x}", selector)).unwrap());
         let solution = generate_vanity_function_name(
-            b"1234",
-            b"", // Empty function name
-            b"",
+            &target,
+            &DenyList::default(),
+            &candidates,
             0,
-            Some(10000),
+            Some(300),
+            Backend::default(),
         );
+        assert_eq!(solution, None);
+    }
 
-        // The test should either find a solution or reach the end
-        if let Some(solution_index) = solution {
-            let function_name = format!("{}()", solution_index);
-            let hash = calculate_keccak_256(function_name.as_bytes());
-            assert_eq!(format!("{:02x}{:02x}", hash[0], hash[1]), "1234");
+    #[test]
+    fn test_max_name_len() {
+        let candidates = Candidates::decimal("mint", "").unwrap().max_name_len(6).unwrap();
+        // Two digits at most
+        assert_eq!(candidates.space(), Some(100));
+        assert_eq!(candidates.check(b"mint100()"), Err(NameError::TooLong { len: 7, max: 6 }));
+        assert_eq!(
+            Candidates::decimal("transfer", "").unwrap().max_name_len(6).unwrap_err(),
+            NameError::TooLong { len: 8, max: 6 }
+        );
+
+        // Longer bases only get the suffixes that still fit
+        let bases = [("mint", ""), ("mintTo", "")];
+        let candidates = Candidates::from_bases(
+            bases,
+            Suffix::Numeral { alphabet: Alphabet::Decimal, width: None },
+        )
+        .unwrap()
+        .max_name_len(6)
+        .unwrap();
+        assert_eq!(candidates.space(), Some(200));
+        assert!(candidates.check(candidates.render(3).as_bytes()).is_err());
+
+        let shortest = generate_shortest_vanity_function_name(
+            &Target::from(Pattern::parse("00").unwrap()),
+            &DenyList::default(),
+            &candidates,
+            Backend::default(),
+        )
+        .unwrap();
+        assert!(shortest.length <= 6);
+    }
+
+    #[test]
+    fn test_joint_search() {
+        let members: Vec<(Target, Candidates)> = [("uint256", "00"), ("uint256,address", "1")]
+            .into_iter()
+            .map(|(parameters, pattern)| {
+                let target = Pattern::parse(pattern).unwrap().into();
+                (target, Candidates::decimal("swap", parameters).unwrap())
+            })
+            .collect();
+        let solution = generate_joint_vanity_function_name(
+            &members,
+            &DenyList::default(),
+            0,
+            Some(1_000_000),
+            Backend::default(),
+        )
+        .unwrap();
+
+        // The same suffix works for both overloads
+        let first = calculate_keccak_256(format!("swap{}(uint256)", solution).as_bytes());
+        let second = calculate_keccak_256(format!("swap{}(uint256,address)", solution).as_bytes());
+        assert_eq!(first[0], 0x00);
+        assert_eq!(second[0] >> 4, 0x1);
+
+        // Each member on its own matches at the same suffix or earlier
+        for (target, candidates) in &members {
+            let end = Some(solution + 1);
+            let alone = generate_vanity_function_name(
+                target,
+                &DenyList::default(),
+                candidates,
+                0,
+                end,
+                Backend::default(),
+            );
+            assert!(alone.is_some());
         }
+
+        // Denying the selector of one member rules the suffix out
+        let deny_list = DenyList::parse(&format!("0x{:08x}", hash_selector(&first))).unwrap();
+        let end = Some(solution + 1);
+        assert_eq!(
+            generate_joint_vanity_function_name(
+                &members,
+                &deny_list,
+                solution,
+                end,
+                Backend::default()
+            ),
+            None
+        );
+        assert_eq!(
+            generate_joint_vanity_function_name::<Candidates>(
+                &[],
+                &deny_list,
+                0,
+                None,
+                Backend::default()
+            ),
+            None
+        );
     }
 
     #[test]
@@ -278,11 +853,12 @@ mod tests {
         let long_params = "address,uint256,string,bytes32,bool,address[],uint256[]";
 
         let solution = generate_vanity_function_name(
-            b"1234",
-            long_name.as_bytes(),
-            long_params.as_bytes(),
+            &Pattern::parse("1234").unwrap().into(),
+            &DenyList::default(),
+            &Candidates::decimal(long_name, long_params).unwrap(),
             0,
             Some(10000), // Limit for test speed
+            Backend::default(),
         );
 
         // Either finds a solution or reaches the limit without panicking
@@ -291,6 +867,33 @@ mod tests {
             let hash = calculate_keccak_256(function_name.as_bytes());
             assert_eq!(format!("{:02x}{:02x}", hash[0], hash[1]), "1234");
         }
+
+        // Signatures of several blocks, with the varying part in the first block or after whole
+        // blocks of the name, which are then absorbed once
+        let tuple = "(address,uint256,bytes32,(address,uint96)[],bool)";
+        let long_params = [tuple; 6].join(",");
+        let long_name = "executeBatchedSwapWithPermitAndCallback".repeat(4);
+        for (fn_name, params) in
+            [(long_name.as_str(), long_params.as_str()), ("swap", &long_params)]
+        {
+            let candidates = Candidates::decimal(fn_name, params).unwrap();
+            assert!(candidates.render(0).len() > 2 * RATE);
+            // Parameters follow the varying part, so long ones alone are absorbed every time
+            let absorbed = SelectorHasher::new(candidates.fixed_prefix()).absorbed();
+            assert_eq!(absorbed, if fn_name == long_name { RATE } else { 0 });
+
+            let solution = generate_vanity_function_name(
+                &Pattern::parse("12").unwrap().into(),
+                &DenyList::default(),
+                &candidates,
+                0,
+                Some(10000),
+                Backend::default(),
+            )
+            .unwrap();
+            let hash = calculate_keccak_256(candidates.render(solution).as_bytes());
+            assert_eq!(hash[0], 0x12);
+        }
     }
 
     #[test]
@@ -308,11 +911,12 @@ mod tests {
 
         // Try to find the function with our generator
         let solution = generate_vanity_function_name(
-            pattern.as_bytes(),
-            fn_name.as_bytes(),
-            fn_params.as_bytes(),
+            &Pattern::parse(&pattern).unwrap().into(),
+            &DenyList::default(),
+            &Candidates::decimal(fn_name, fn_params).unwrap(),
             0,
             Some(100000), // Should find the match within this range
+            Backend::default(),
         );
 
         assert!(solution.is_some());
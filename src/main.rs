@@ -1,11 +1,33 @@
-use std::{sync::atomic::Ordering, time::Instant};
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
 
 use gumdrop::Options;
 use log::{error, info, warn};
 use vanity_4b::{
-    HASH_COUNTER, HEX_LOOKUP_TABLE, calculate_keccak_256, cli::Opts, generate_vanity_function_name,
+    HASH_COUNTER,
+    alphabet::Alphabet,
+    backend::{Backend, differential_check},
+    candidate::{CandidateGenerator, Candidates, MAX_WIDTH, PLACEHOLDER, Suffix},
+    cli::Opts,
+    deny_list::DenyList,
+    expr::parse_expression,
+    generate_joint_vanity_function_name, generate_shortest_vanity_function_name,
+    generate_vanity_function_name,
+    keccak::RATE,
+    optimize_vanity_function_name,
+    pattern::Pattern,
+    score::Objective,
+    selector_set::SelectorSet,
+    style::style_variants,
+    target::{Target, calldata_gas, zero_bytes},
+    wordlist::Wordlists,
 };
 
+/// Candidates hashed by every implementation before a search starts
+const DIFFERENTIAL_CHECK: u64 = 4096;
+
 fn main() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
     let opts = Opts::parse_args_default_or_exit();
@@ -19,60 +41,81 @@ fn main() {
         .build_global()
         .expect("Failed to build thread pool");
 
-    // Lower case and strip '0x' if pattern starts with it
-    let pattern = opts.pattern.to_lowercase();
-    let mut pattern_without_prefix = pattern.strip_prefix("0x").unwrap_or(&pattern);
+    let fn_names: Vec<&str> = opts.fn_name.split(',').map(str::trim).collect();
+    let fn_parameters = opts.fn_parameters.as_deref().unwrap_or_default();
+    let fn_parameters: Vec<&str> = fn_parameters.split(';').map(str::trim).collect();
+    let full_names: Vec<String> = fn_names
+        .iter()
+        .flat_map(|name| {
+            fn_parameters.iter().map(move |parameters| format!("{}({})", name, parameters))
+        })
+        .collect();
+    info!("Start searching vanity function name for {}", full_names.join(", "));
+    info!("Using {} threads on {} physical cores for processing", threads_to_use, available_cores);
+    let backend = opts.backend.unwrap_or_default();
+    info!("Hashing with the {} Keccak-256 backend", backend);
+    #[cfg(feature = "portable")]
+    if backend == Backend::Portable {
+        info!("Keccak kernel: {}", vanity_4b::keccak::Kernel::detect());
+    }
 
-    // Validate pattern
-    // It should be <= 8 chars
-    if pattern_without_prefix.len() > 8 {
-        let truncated_pattern_without_prefix = &pattern_without_prefix[..8];
+    let deny_list = build_deny_list(&opts);
+    let candidates = build_candidates(&opts, &fn_names, &fn_parameters);
+    if Backend::ALL.len() < 2 {
         warn!(
-            "Pattern {} has invalid lenght. Truncating to 0x{}",
-            opts.pattern, truncated_pattern_without_prefix
+            "Only the {} Keccak-256 backend is compiled in, checking known selectors only",
+            backend
         );
-        pattern_without_prefix = truncated_pattern_without_prefix;
     }
-    // Every char should be in 0123456789abcdef range
-    if !pattern_without_prefix.chars().all(|c| HEX_LOOKUP_TABLE[c as usize] != 0xFF) {
-        error!("Pattern {} has invalid characters!", opts.pattern);
-        std::process::exit(1);
+    match differential_check(&candidates, DIFFERENTIAL_CHECK, backend) {
+        Ok(checked) => info!("Keccak-256 implementations agree on {} signatures", checked),
+        Err(e) => {
+            error!("Keccak-256 implementations disagree, not searching: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let (signature_len, fixed) = (candidates.render(0).len(), candidates.fixed_prefix());
+    if fixed >= RATE {
+        info!("Absorbing the first {} bytes of every signature once", fixed / RATE * RATE);
+    } else if signature_len >= RATE {
+        // Parameters come after the varying part, only a name of a block or more can help
+        warn!(
+            "Signatures span {} Keccak blocks and every one is absorbed per candidate, only the \
+             part of the name before {{}} is absorbed once and it is shorter than {} bytes",
+            signature_len / RATE + 1,
+            RATE
+        );
     }
-    // Done validating pattern
-
-    let fn_name = &opts.fn_name;
-    let fn_parameters = &opts.fn_parameters.unwrap_or_default();
-    let full_name = format!("{}({})", fn_name, fn_parameters);
-    info!("Start searching vanity function name for {}", full_name);
-    info!("Using {} threads on {} physical cores for processing", threads_to_use, available_cores);
 
     let instant = Instant::now();
 
-    let step = 1_000_000_000_u64;
-    for starting_point in (0..u64::MAX).step_by(step as usize) {
-        let ending_point = starting_point.saturating_add(step);
-        info!("Range: [{}..{}]", starting_point, ending_point);
-        match generate_vanity_function_name(
-            pattern_without_prefix.as_bytes(),
-            fn_name.as_bytes(),
-            fn_parameters.as_bytes(),
-            starting_point,
-            Some(ending_point),
-        ) {
-            Some(solution_index) => {
-                let vanity_function_name =
-                    format!("{}{}({})", fn_name, solution_index, fn_parameters);
-                let hash = calculate_keccak_256(vanity_function_name.as_bytes());
-                let signature =
-                    format!("0x{:02x}{:02x}{:02x}{:02x}", hash[0], hash[1], hash[2], hash[3]);
-                info!("Vanity function name found:");
-                info!("Signature: {}", signature);
-                info!("Function name: {}", vanity_function_name);
-                break;
+    match opts.optimize {
+        Some(objective) => {
+            if opts.shortest || opts.joint {
+                error!("--optimize ranks single candidates, use neither --shortest nor --joint");
+                std::process::exit(1);
             }
-            None => {
-                warn!("Did not find solution");
+            if targets_given(&opts) > 0 {
+                error!("--optimize ranks every selector, it can not be combined with a target");
+                std::process::exit(1);
+            }
+            optimize(&opts, objective, &deny_list, &candidates, backend);
+        }
+        None if opts.joint => {
+            if opts.shortest {
+                error!("--shortest can not be combined with --joint");
+                std::process::exit(1);
             }
+            let targets = build_joint_targets(&opts, candidates.bases());
+            joint(targets, &deny_list, &candidates, backend, instant);
+        }
+        None if opts.shortest => {
+            let target = build_target(&opts);
+            shortest(&target, &deny_list, &candidates, backend);
+        }
+        None => {
+            let target = build_target(&opts);
+            search(&target, &deny_list, &candidates, backend, instant);
         }
     }
 
@@ -84,3 +127,482 @@ fn main() {
     info!("Elapsed time {} seconds", elapsed_seconds);
     info!("Summary: {} hashes, average speed: {:.2} MH/s", total_hashes, mhps);
 }
+
+fn search(
+    target: &Target,
+    deny_list: &DenyList,
+    candidates: &Candidates,
+    backend: Backend,
+    instant: Instant,
+) {
+    info!("Difficulty: 1 in {:.0} hashes on average", target.difficulty());
+
+    let space_end = candidates.range_end(None);
+    let solution = search_ranges(space_end, target.difficulty(), instant, |start, end| {
+        generate_vanity_function_name(target, deny_list, candidates, start, Some(end), backend)
+    });
+    match solution {
+        Some(solution_index) => report(target, candidates, solution_index, backend),
+        None => exhausted(candidates, space_end),
+    }
+}
+
+/// Looks for one suffix that gives every base of `candidates` a match for its own target
+fn joint(
+    targets: Vec<Target>,
+    deny_list: &DenyList,
+    candidates: &Candidates,
+    backend: Backend,
+    instant: Instant,
+) {
+    let members: Vec<(Target, Candidates)> = targets
+        .into_iter()
+        .enumerate()
+        .map(|(base, target)| (target, candidates.base_only(base)))
+        .collect();
+    let difficulty: f64 = members.iter().map(|(target, _)| target.difficulty()).product();
+    info!("Joint search over {} signatures sharing one suffix", members.len());
+    info!("Difficulty: 1 in {:.0} suffixes on average", difficulty);
+
+    let space_end = members[0].1.range_end(None);
+    let solution = search_ranges(space_end, difficulty, instant, |start, end| {
+        generate_joint_vanity_function_name(&members, deny_list, start, Some(end), backend)
+    });
+    let Some(suffix) = solution else {
+        exhausted(candidates, space_end);
+    };
+
+    info!("Joint vanity suffix found: {:?}", candidates.suffix().render(suffix));
+    for (target, member) in &members {
+        report(target, member, suffix, backend);
+    }
+}
+
+/// Runs `find` over `0..space_end` in chunks, logging progress between them
+fn search_ranges(
+    space_end: u64,
+    difficulty: f64,
+    instant: Instant,
+    mut find: impl FnMut(u64, u64) -> Option<u64>,
+) -> Option<u64> {
+    let step = 1_000_000_000_u64;
+    for starting_point in (0..space_end).step_by(step as usize) {
+        let ending_point = starting_point.saturating_add(step).min(space_end);
+        info!("Range: [{}..{}]", starting_point, ending_point);
+        if let Some(solution) = find(starting_point, ending_point) {
+            return Some(solution);
+        }
+        warn!("Did not find solution");
+        if ending_point == space_end {
+            break;
+        }
+        let elapsed_seconds = instant.elapsed().as_secs_f64();
+        let hashes_per_second = HASH_COUNTER.load(Ordering::Relaxed) as f64 / elapsed_seconds;
+        if hashes_per_second > 0.0 {
+            info!(
+                "ETA: ~{:.0} seconds per expected match at {:.2} MH/s",
+                difficulty / hashes_per_second,
+                hashes_per_second / 1_000_000.0
+            );
+        }
+    }
+    None
+}
+
+/// Every candidate was tried without a match
+fn exhausted(candidates: &Candidates, space_end: u64) -> ! {
+    // Only bounded suffixes end before u64::MAX, longer names are not tried on their own
+    let hint = match candidates.suffix() {
+        Suffix::Numeral { width: Some(_), .. } => "use a larger --width",
+        Suffix::Numeral { width: None, .. } => "allow a larger --max-name-len",
+        Suffix::Words(_) => "add more words or lists",
+        Suffix::None => "allow a suffix",
+    };
+    error!("Searched all {} candidates without a match, {}", space_end, hint);
+    std::process::exit(1);
+}
+
+fn shortest(target: &Target, deny_list: &DenyList, candidates: &Candidates, backend: Backend) {
+    info!("Difficulty: 1 in {:.0} hashes on average", target.difficulty());
+    info!("Searching every name length completely before the next one");
+
+    let Some(shortest) =
+        generate_shortest_vanity_function_name(target, deny_list, candidates, backend)
+    else {
+        error!("--shortest can not order word combinations by length");
+        std::process::exit(1);
+    };
+    match shortest.index {
+        Some(index) => {
+            report(target, candidates, index, backend);
+            info!(
+                "Shortest possible name: {} characters, {} shorter lengths exhausted",
+                shortest.length, shortest.exhausted
+            );
+        }
+        None => {
+            error!(
+                "Searched every name up to {} characters without a match, {} lengths exhausted",
+                shortest.length, shortest.exhausted
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Logs a match and everything known about it
+fn report(target: &Target, candidates: &Candidates, index: u64, backend: Backend) {
+    let vanity_function_name = candidates.render(index);
+    let hash = backend.keccak_256(vanity_function_name.as_bytes());
+    let signature = format!("0x{:02x}{:02x}{:02x}{:02x}", hash[0], hash[1], hash[2], hash[3]);
+    info!("Vanity function name found:");
+    info!("Signature: {}", signature);
+    let selector = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
+    info!(
+        "Calldata gas: {} per call ({} zero bytes)",
+        calldata_gas(selector),
+        zero_bytes(selector)
+    );
+    if let Target::Set(set) = target {
+        info!("Matched target {} out of {} acceptable selectors", signature, set.len());
+    }
+    info!("Function name: {}", vanity_function_name);
+    if candidates.bases() > 1 {
+        let base = candidates.base(index);
+        let (template, parameters) = candidates.label(base);
+        info!("Base: {}({}), {} of {}", template, parameters, base + 1, candidates.bases());
+    }
+    if let Some(words) = candidates.words(index) {
+        info!("Words: {}", words.join(", "));
+    }
+}
+
+fn optimize(
+    opts: &Opts,
+    objective: Objective,
+    deny_list: &DenyList,
+    candidates: &Candidates,
+    backend: Backend,
+) {
+    let budget = Duration::from_secs(opts.time_budget.unwrap_or(600));
+    let top_k = opts.top.unwrap_or(10);
+    if top_k == 0 {
+        error!("--top must keep at least one candidate");
+        std::process::exit(1);
+    }
+    info!("Optimizing {:?} for {} seconds, keeping the top {}", objective, budget.as_secs(), top_k);
+
+    let deadline = Instant::now() + budget;
+    let top = optimize_vanity_function_name(
+        &objective, deny_list, candidates, 0, None, deadline, top_k, backend,
+    );
+
+    if Instant::now() < deadline {
+        info!("Searched all {} candidates, best candidates:", candidates.range_end(None));
+    } else {
+        info!("Time budget expired, best candidates:");
+    }
+    for (rank, scored) in top.iter().enumerate() {
+        info!(
+            "#{} score {}: 0x{:08x} {}",
+            rank + 1,
+            scored.score,
+            scored.selector,
+            candidates.render(scored.index)
+        );
+    }
+}
+
+fn build_candidates(opts: &Opts, fn_names: &[&str], fn_parameters: &[&str]) -> Candidates {
+    let suffixes_given = [!opts.wordlist.is_empty(), opts.no_suffix, opts.alphabet.is_some()];
+    if suffixes_given.iter().filter(|&&given| given).count() > 1 {
+        error!("Use only one of --wordlist, --no-suffix or --alphabet");
+        std::process::exit(1);
+    }
+    if opts.width.is_some() && (!opts.wordlist.is_empty() || opts.no_suffix) {
+        error!("--width only applies to numeric suffixes");
+        std::process::exit(1);
+    }
+
+    let suffix = if !opts.wordlist.is_empty() {
+        match Wordlists::from_files(&opts.wordlist) {
+            Ok(Some(wordlists)) => {
+                info!("Combining {} wordlists", wordlists.len());
+                Suffix::Words(wordlists)
+            }
+            Ok(None) => {
+                error!("Every wordlist needs at least one word");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                error!("Failed to read wordlists: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if opts.no_suffix {
+        Suffix::None
+    } else {
+        let alphabet = opts.alphabet.clone().unwrap_or_default();
+        if alphabet != Alphabet::Decimal {
+            info!("Suffix alphabet: {}", String::from_utf8_lossy(alphabet.symbols()));
+        }
+        Suffix::Numeral { alphabet, width: None }
+    };
+
+    let mut names: Vec<String> = Vec::new();
+    if opts.styles {
+        if fn_names.iter().any(|name| name.contains(PLACEHOLDER)) {
+            error!("--styles rewrites plain function names, not templates");
+            std::process::exit(1);
+        }
+        let split = |words: &Option<String>| -> Vec<String> {
+            let words = words.as_deref().unwrap_or_default();
+            words.split(',').map(str::trim).filter(|w| !w.is_empty()).map(String::from).collect()
+        };
+        for fn_name in fn_names {
+            let variants =
+                style_variants(fn_name, &split(&opts.prefix_words), &split(&opts.suffix_words));
+            info!("Trying {} style variants of {}", variants.len(), fn_name);
+            for variant in variants {
+                if !names.contains(&variant) {
+                    names.push(variant);
+                }
+            }
+        }
+    } else {
+        if opts.prefix_words.is_some() || opts.suffix_words.is_some() {
+            error!("--prefix-words and --suffix-words can only be used together with --styles");
+            std::process::exit(1);
+        }
+        names.extend(fn_names.iter().map(|name| name.to_string()));
+    }
+
+    // Never empty, there is at least one name and one parameter list
+    let bases = names
+        .iter()
+        .flat_map(|name| fn_parameters.iter().map(move |parameters| (name.as_str(), *parameters)));
+    let mut candidates = match Candidates::from_bases(bases, suffix) {
+        Ok(candidates) => candidates,
+        Err(e) => {
+            error!("Invalid function name or parameters: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if candidates.bases() > 1 {
+        info!("Interleaving {} base names and parameter lists", candidates.bases());
+    }
+    if let Some(width) = opts.width {
+        candidates = match candidates.fixed_width(width) {
+            Some(candidates) => candidates,
+            None => {
+                error!("--width must be between 1 and {}", MAX_WIDTH);
+                std::process::exit(1);
+            }
+        };
+        info!("Fixed width of {} symbols", width);
+    }
+    if let Some(max) = opts.max_name_len {
+        candidates = match candidates.max_name_len(max) {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                error!("--max-name-len {}: {}", max, e);
+                std::process::exit(1);
+            }
+        };
+        info!("Function names of at most {} characters", max);
+    }
+    if let Some(space) = candidates.space() {
+        info!("{} candidates in total", space);
+    }
+    candidates
+}
+
+fn build_deny_list(opts: &Opts) -> DenyList {
+    // Inline entries and the file share one syntax, so parse them together
+    let mut entries = opts.deny.clone().unwrap_or_default();
+    if let Some(path) = &opts.deny_file {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                entries.push('\n');
+                entries.push_str(&contents);
+            }
+            Err(e) => {
+                error!("Failed to read deny list from {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    match DenyList::parse(&entries) {
+        Ok(deny_list) => {
+            if !deny_list.is_empty() {
+                info!("Excluding {} denied selectors and patterns", deny_list.len());
+            }
+            deny_list
+        }
+        Err(e) => {
+            error!("Invalid deny list entry {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Number of distinct target kinds given on the command line
+fn targets_given(opts: &Opts) -> usize {
+    let range_given = opts.below.is_some() || opts.above.is_some() || opts.between.is_some();
+    [
+        opts.pattern.is_some(),
+        opts.mask.is_some(),
+        range_given,
+        opts.targets_file.is_some(),
+        opts.zero_bytes.is_some(),
+        opts.expr.is_some() || opts.expr_file.is_some(),
+    ]
+    .iter()
+    .filter(|&&given| given)
+    .count()
+}
+
+/// One target per signature of a joint search, `;` separated patterns go to the signatures in
+/// order, any other target applies to all of them
+fn build_joint_targets(opts: &Opts, signatures: usize) -> Vec<Target> {
+    if signatures < 2 {
+        error!("--joint needs several signatures, give more names or parameter lists");
+        std::process::exit(1);
+    }
+    let patterns: Vec<&str> = match &opts.pattern {
+        Some(pattern) if pattern.contains(';') => pattern.split(';').map(str::trim).collect(),
+        _ => return vec![build_target(opts); signatures],
+    };
+    if patterns.len() != signatures {
+        error!("Got {} patterns for {} signatures", patterns.len(), signatures);
+        std::process::exit(1);
+    }
+    patterns
+        .into_iter()
+        .map(|pattern| build_target(&Opts { pattern: Some(pattern.to_string()), ..opts.clone() }))
+        .collect()
+}
+
+fn build_target(opts: &Opts) -> Target {
+    match targets_given(opts) {
+        0 => {
+            error!(
+                "Missing target, use a pattern, --mask/--value, --below/--above/--between, \
+                 --targets-file, --zero-bytes or --expr"
+            );
+            std::process::exit(1);
+        }
+        1 => {}
+        _ => {
+            error!(
+                "Use only one of a pattern, --mask/--value, --below/--above/--between, \
+                 --targets-file, --zero-bytes or --expr"
+            );
+            std::process::exit(1);
+        }
+    }
+    if opts.value.is_some() && opts.mask.is_none() {
+        error!("--value can only be used together with --mask");
+        std::process::exit(1);
+    }
+    if opts.anchor.is_some() && opts.pattern.is_none() {
+        error!("--anchor can only be used together with a pattern");
+        std::process::exit(1);
+    }
+
+    if let Some(pattern) = &opts.pattern {
+        return match Pattern::parse_anchored(pattern, opts.anchor.unwrap_or_default()) {
+            Ok(pattern) => pattern.into(),
+            Err(e) => {
+                error!("Invalid pattern {}: {}", pattern, e);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    if let Some(mask) = opts.mask {
+        let value = opts.value.unwrap_or_default();
+        return match Pattern::from_mask_value(mask, value) {
+            Ok(pattern) => pattern.into(),
+            Err(e) => {
+                error!("Invalid mask/value: {}", e);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    if let Some(path) = &opts.targets_file {
+        let set = match SelectorSet::from_file(path) {
+            Ok(set) => set,
+            Err(e) => {
+                error!("Failed to read targets from {}: {}", path, e);
+                std::process::exit(1);
+            }
+        };
+        if set.is_empty() {
+            error!("No targets found in {}", path);
+            std::process::exit(1);
+        }
+        info!("Loaded {} acceptable selectors from {}", set.len(), path);
+        return set.into();
+    }
+
+    if opts.expr.is_some() && opts.expr_file.is_some() {
+        error!("Use either --expr or --expr-file, not both");
+        std::process::exit(1);
+    }
+    let expression = match (&opts.expr, &opts.expr_file) {
+        (Some(expression), _) => Some(expression.clone()),
+        (None, Some(path)) => match std::fs::read_to_string(path) {
+            Ok(expression) => Some(expression),
+            Err(e) => {
+                error!("Failed to read expression from {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        (None, None) => None,
+    };
+    if let Some(expression) = expression {
+        return match parse_expression(&expression) {
+            Ok(target) => target,
+            Err(e) => {
+                error!("Invalid match expression {}", e);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    if let Some(count) = opts.zero_bytes {
+        let Some(target) = Target::zero_bytes(count) else {
+            error!("A selector has only 4 bytes, can not have {} zero bytes!", count);
+            std::process::exit(1);
+        };
+        return target;
+    }
+
+    // --below and --above may be combined into a range, --between stands alone
+    let (low, high) = match opts.between {
+        Some(_) if opts.below.is_some() || opts.above.is_some() => {
+            error!("--between can not be combined with --below/--above");
+            std::process::exit(1);
+        }
+        Some(range) => range,
+        None => {
+            let low = opts.above.map(|above| above.checked_add(1));
+            let high = opts.below.map(|below| below.checked_sub(1));
+            match (low.unwrap_or(Some(0)), high.unwrap_or(Some(u32::MAX))) {
+                (Some(low), Some(high)) => (low, high),
+                _ => {
+                    error!("Selector range is empty!");
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+    let Some(target) = Target::between(low, high) else {
+        error!("Selector range is empty!");
+        std::process::exit(1);
+    };
+    target
+}
@@ -0,0 +1,353 @@
+use std::{fmt, str::FromStr};
+
+use crate::HEX_LOOKUP_TABLE;
+
+/// Characters accepted as "any nibble" in a pattern
+pub const WILDCARDS: [u8; 2] = [b'?', b'x'];
+
+/// Where a pattern has to sit inside the 8 nibbles of the selector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    /// Selector starts with the pattern
+    #[default]
+    Prefix,
+    /// Selector ends with the pattern
+    Suffix,
+    /// Pattern starts at the given nibble offset
+    Offset(u32),
+    /// Pattern may start at any nibble offset
+    Anywhere,
+}
+
+impl FromStr for Pattern {
+    type Err = PatternError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        Self::parse(pattern)
+    }
+}
+
+impl FromStr for Anchor {
+    type Err = String;
+
+    /// Parses `prefix`, `suffix`, `anywhere` or a nibble offset such as `2`
+    fn from_str(anchor: &str) -> Result<Self, Self::Err> {
+        match anchor {
+            "prefix" => Ok(Anchor::Prefix),
+            "suffix" => Ok(Anchor::Suffix),
+            "anywhere" => Ok(Anchor::Anywhere),
+            offset => match offset.parse::<u32>() {
+                Ok(offset) if offset < 8 => Ok(Anchor::Offset(offset)),
+                _ => Err(format!("invalid anchor {:?}", anchor)),
+            },
+        }
+    }
+}
+
+/// Reasons a pattern can not be compiled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// More than the 8 nibbles of a selector
+    TooLong(usize),
+    /// Neither a hex digit nor a wildcard
+    InvalidCharacter(char),
+    /// Pattern of `len` nibbles runs past the end of the selector at `offset`
+    DoesNotFit { len: u32, offset: u32 },
+    /// Value has bits set outside of the mask
+    ValueOutsideMask { mask: u32, value: u32 },
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::TooLong(len) => {
+                write!(f, "pattern has {} nibbles, a selector only has 8", len)
+            }
+            PatternError::InvalidCharacter(c) => write!(f, "invalid character {:?} in pattern", c),
+            PatternError::DoesNotFit { len, offset } => {
+                write!(f, "pattern of {} nibbles does not fit at offset {}", len, offset)
+            }
+            PatternError::ValueOutsideMask { mask, value } => {
+                write!(f, "value 0x{:08x} has bits outside of mask 0x{:08x}", value, mask)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// A selector pattern compiled to a mask/value pair over the first 4 hash bytes.
+///
+/// Every fixed nibble sets `0xF` in the mask at its position, wildcards leave it at `0`, so a
+/// selector matches when `(selector & mask) == value`. Floating patterns additionally try the
+/// pattern at each of the `slides` following nibble offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Pattern {
+    mask: u32,
+    value: u32,
+    slides: u32,
+}
+
+impl Pattern {
+    /// Parses a pattern such as `0x12??56?8`, the `0x` prefix is optional and case is ignored.
+    ///
+    /// Fails if the pattern is longer than 8 nibbles or contains a character that is neither a
+    /// hex digit nor one of [`WILDCARDS`].
+    pub fn parse(pattern: &str) -> Result<Self, PatternError> {
+        Self::parse_anchored(pattern, Anchor::Prefix)
+    }
+
+    /// Same as [`Pattern::parse`], placing the pattern according to `anchor`.
+    ///
+    /// Also fails if the pattern does not fit at the given offset.
+    pub fn parse_anchored(pattern: &str, anchor: Anchor) -> Result<Self, PatternError> {
+        let pattern = pattern.strip_prefix("0x").or(pattern.strip_prefix("0X")).unwrap_or(pattern);
+        let len = pattern.len() as u32;
+        if len > 8 {
+            return Err(PatternError::TooLong(pattern.len()));
+        }
+
+        let mut mask = 0u32;
+        let mut value = 0u32;
+        for (i, c) in pattern.bytes().enumerate() {
+            let shift = 28 - 4 * i as u32;
+            if WILDCARDS.contains(&c.to_ascii_lowercase()) {
+                continue;
+            }
+            let nibble = HEX_LOOKUP_TABLE[c as usize];
+            if nibble == 0xFF {
+                return Err(PatternError::InvalidCharacter(pattern[i..].chars().next().unwrap()));
+            }
+            mask |= 0xF << shift;
+            value |= (nibble as u32) << shift;
+        }
+
+        // Prefix aligned so far, move it to the requested position
+        let (offset, slides) = match anchor {
+            Anchor::Prefix => (0, 0),
+            Anchor::Suffix => (8 - len, 0),
+            Anchor::Offset(offset) if offset.checked_add(len).is_some_and(|end| end <= 8) => {
+                (offset, 0)
+            }
+            Anchor::Offset(offset) => return Err(PatternError::DoesNotFit { len, offset }),
+            Anchor::Anywhere if len == 0 => (0, 0),
+            Anchor::Anywhere => (0, 8 - len),
+        };
+        let shift = 4 * offset;
+        Ok(Self {
+            mask: mask.checked_shr(shift).unwrap_or(0),
+            value: value.checked_shr(shift).unwrap_or(0),
+            slides,
+        })
+    }
+
+    /// Builds a pattern from a bit mask and the value the masked selector must equal.
+    ///
+    /// Fails if `value` has bits set outside of `mask`, as nothing could ever match.
+    pub fn from_mask_value(mask: u32, value: u32) -> Result<Self, PatternError> {
+        if value & !mask != 0 {
+            return Err(PatternError::ValueOutsideMask { mask, value });
+        }
+        Ok(Self { mask, value, slides: 0 })
+    }
+
+    #[inline]
+    pub fn matches(&self, selector: u32) -> bool {
+        if self.slides == 0 {
+            return (selector & self.mask) == self.value;
+        }
+        (0..=self.slides).any(|slide| ((selector << (4 * slide)) & self.mask) == self.value)
+    }
+
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Number of nibbles that are not wildcards
+    pub fn fixed_nibbles(&self) -> u32 {
+        self.mask.count_ones() / 4
+    }
+
+    /// Number of selector bits pinned by the mask
+    pub fn fixed_bits(&self) -> u32 {
+        self.mask.count_ones()
+    }
+
+    /// Probability that a random selector matches.
+    ///
+    /// Floating patterns use inclusion-exclusion over every combination of placements, as the
+    /// placements overlap and are not independent.
+    pub fn probability(&self) -> f64 {
+        // Placements in selector coordinates
+        let placements: Vec<(u32, u32)> = (0..=self.slides)
+            .map(|slide| (self.mask >> (4 * slide), self.value >> (4 * slide)))
+            .collect();
+
+        let mut probability = 0.0;
+        for subset in 1u32..(1 << placements.len()) {
+            let mut mask = 0u32;
+            let mut value = 0u32;
+            let mut consistent = true;
+            for (i, &(m, v)) in placements.iter().enumerate() {
+                if subset & (1 << i) == 0 {
+                    continue;
+                }
+                if (value ^ v) & mask & m != 0 {
+                    consistent = false;
+                    break;
+                }
+                mask |= m;
+                value |= v;
+            }
+            if consistent {
+                let term = 0.5f64.powi(mask.count_ones() as i32);
+                probability += if subset.count_ones() % 2 == 1 { term } else { -term };
+            }
+        }
+        probability
+    }
+
+    /// Expected number of hashes needed to find a match
+    pub fn difficulty(&self) -> f64 {
+        1.0 / self.probability()
+    }
+}
+
+/// Parses a 4-byte selector written in hex, with or without a `0x` or `0X` prefix
+pub fn parse_selector(selector: &str) -> Result<u32, std::num::ParseIntError> {
+    let selector = selector.strip_prefix("0x").or(selector.strip_prefix("0X")).unwrap_or(selector);
+    u32::from_str_radix(selector, 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Anchor, Pattern, PatternError, parse_selector};
+
+    #[test]
+    fn test_parse_wildcards() {
+        let pattern = Pattern::parse("12??56?8").unwrap();
+        assert_eq!(pattern.mask(), 0xFF00FF0F);
+        assert_eq!(pattern.value(), 0x12005608);
+        assert_eq!(pattern.fixed_nibbles(), 5);
+
+        // `x` is the same wildcard as `?`
+        assert_eq!(Pattern::parse("deadxxef"), Pattern::parse("dead??ef"));
+        // Prefix and case do not matter
+        assert_eq!(Pattern::parse("0xDEADXXEF"), Pattern::parse("dead??ef"));
+
+        assert!(pattern.matches(0x12345678));
+        assert!(pattern.matches(0x12ab56c8));
+        assert!(!pattern.matches(0x13345678));
+        assert!(!pattern.matches(0x12345679));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(Pattern::parse("123456789"), Err(PatternError::TooLong(9)));
+        assert_eq!(Pattern::parse("0x123456789"), Err(PatternError::TooLong(9)));
+        assert_eq!(Pattern::parse("12g4"), Err(PatternError::InvalidCharacter('g')));
+        assert_eq!(Pattern::parse("12é4"), Err(PatternError::InvalidCharacter('é')));
+        assert_eq!(
+            Pattern::parse_anchored("beef", Anchor::Offset(5)),
+            Err(PatternError::DoesNotFit { len: 4, offset: 5 })
+        );
+        // Library callers can pass any offset, it must not overflow
+        assert_eq!(
+            Pattern::parse_anchored("beef", Anchor::Offset(u32::MAX)),
+            Err(PatternError::DoesNotFit { len: 4, offset: u32::MAX })
+        );
+        assert_eq!(
+            Pattern::from_mask_value(0xFF000000, 0x00000001),
+            Err(PatternError::ValueOutsideMask { mask: 0xFF000000, value: 0x00000001 })
+        );
+        assert_eq!("0x12".parse::<Pattern>(), Pattern::parse("12"));
+    }
+
+    #[test]
+    fn test_difficulty() {
+        assert_eq!(Pattern::parse("").unwrap().difficulty(), 1.0);
+        assert_eq!(Pattern::parse("1234").unwrap().difficulty(), 65536.0);
+        // Wildcards do not add to the difficulty
+        assert_eq!(Pattern::parse("12????34").unwrap().difficulty(), 65536.0);
+        // Mask difficulty counts bits, not nibbles
+        assert_eq!(Pattern::from_mask_value(0xE0000000, 0).unwrap().difficulty(), 8.0);
+    }
+
+    #[test]
+    fn test_mask_value() {
+        // Top 3 bits zero
+        let pattern = Pattern::from_mask_value(0xE0000000, 0).unwrap();
+        assert!(pattern.matches(0x1fffffff));
+        assert!(!pattern.matches(0x20000000));
+
+        // Lowest 5 bits equal 0b10110
+        let pattern = Pattern::from_mask_value(0x1F, 0b10110).unwrap();
+        assert!(pattern.matches(0xabcdef16));
+        assert!(pattern.matches(0x00000036));
+        assert!(!pattern.matches(0x00000017));
+
+        // Value bits outside of the mask can never match
+        assert!(Pattern::from_mask_value(0xFF000000, 0x00000001).is_err());
+    }
+
+    #[test]
+    fn test_anchors() {
+        let suffix = Pattern::parse_anchored("beef", Anchor::Suffix).unwrap();
+        assert!(suffix.matches(0x1234beef));
+        assert!(!suffix.matches(0xbeef1234));
+        assert_eq!(suffix.difficulty(), 65536.0);
+
+        let offset = Pattern::parse_anchored("be?f", Anchor::Offset(2)).unwrap();
+        assert!(offset.matches(0x12be3f78));
+        assert!(!offset.matches(0xbe3f1278));
+        assert_eq!(offset.difficulty(), 4096.0);
+        assert!(Pattern::parse_anchored("beef", Anchor::Offset(5)).is_err());
+
+        let anywhere = Pattern::parse_anchored("beef", Anchor::Anywhere).unwrap();
+        assert!(anywhere.matches(0xbeef1234));
+        assert!(anywhere.matches(0x12beef34));
+        assert!(anywhere.matches(0x1234beef));
+        assert!(!anywhere.matches(0x12be3ef4));
+    }
+
+    #[test]
+    fn test_anywhere_probability() {
+        // 5 placements that can not overlap for "beef", minus the few double hits
+        let anywhere = Pattern::parse_anchored("beef", Anchor::Anywhere).unwrap();
+        let expected = 5.0 / 65536.0 - 1.0 / 65536.0 / 65536.0;
+        assert!((anywhere.probability() - expected).abs() < 1e-15);
+
+        // Overlapping placements: count nibble strings without "00",
+        // f(n) = 15 f(n-1) + 15 f(n-2)
+        let anywhere = Pattern::parse_anchored("00", Anchor::Anywhere).unwrap();
+        let mut without = [1.0f64, 16.0];
+        for _ in 2..=8 {
+            without = [without[1], 15.0 * without[1] + 15.0 * without[0]];
+        }
+        let expected = 1.0 - without[1] / 16f64.powi(8);
+        assert!((anywhere.probability() - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parse_anchor() {
+        assert_eq!("prefix".parse(), Ok(Anchor::Prefix));
+        assert_eq!("suffix".parse(), Ok(Anchor::Suffix));
+        assert_eq!("anywhere".parse(), Ok(Anchor::Anywhere));
+        assert_eq!("3".parse(), Ok(Anchor::Offset(3)));
+        assert!("8".parse::<Anchor>().is_err());
+        assert!("middle".parse::<Anchor>().is_err());
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        assert_eq!(parse_selector("0xffe00000"), Ok(0xffe00000));
+        assert_eq!(parse_selector("1F"), Ok(0x1f));
+        assert_eq!(parse_selector("0X01000000"), Ok(0x01000000));
+        assert!(parse_selector("0x1ffffffff").is_err());
+        assert!(parse_selector("0xzz").is_err());
+    }
+}
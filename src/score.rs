@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use crate::target::zero_bytes;
+
+/// Ranks selectors for the best-so-far search, higher is better
+pub trait Score: Sync {
+    fn score(&self, selector: u32) -> u32;
+}
+
+/// Number of leading zero bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeadingZeroBits;
+
+impl Score for LeadingZeroBits {
+    #[inline]
+    fn score(&self, selector: u32) -> u32 {
+        selector.leading_zeros()
+    }
+}
+
+/// Number of zero bytes in any position, i.e. cheapest calldata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZeroBytes;
+
+impl Score for ZeroBytes {
+    #[inline]
+    fn score(&self, selector: u32) -> u32 {
+        zero_bytes(selector)
+    }
+}
+
+/// Numerically smallest selector, e.g. to sort first in the dispatcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Smallness;
+
+impl Score for Smallness {
+    #[inline]
+    fn score(&self, selector: u32) -> u32 {
+        !selector
+    }
+}
+
+/// The built-in scoring functions, selectable by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    LeadingZeroBits,
+    ZeroBytes,
+    Smallness,
+}
+
+impl Score for Objective {
+    #[inline]
+    fn score(&self, selector: u32) -> u32 {
+        match self {
+            Objective::LeadingZeroBits => LeadingZeroBits.score(selector),
+            Objective::ZeroBytes => ZeroBytes.score(selector),
+            Objective::Smallness => Smallness.score(selector),
+        }
+    }
+}
+
+impl FromStr for Objective {
+    type Err = String;
+
+    /// Parses `leading-zero-bits`, `zero-bytes` or `smallness`
+    fn from_str(objective: &str) -> Result<Self, Self::Err> {
+        match objective {
+            "leading-zero-bits" => Ok(Objective::LeadingZeroBits),
+            "zero-bytes" => Ok(Objective::ZeroBytes),
+            "smallness" => Ok(Objective::Smallness),
+            _ => Err(format!("invalid objective {:?}", objective)),
+        }
+    }
+}
+
+/// A candidate kept by the best-so-far search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scored {
+    pub index: u64,
+    pub selector: u32,
+    pub score: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LeadingZeroBits, Objective, Score, Smallness, ZeroBytes};
+
+    #[test]
+    fn test_scores() {
+        assert_eq!(LeadingZeroBits.score(0x00012345), 15);
+        assert_eq!(LeadingZeroBits.score(0x80000000), 0);
+        assert_eq!(ZeroBytes.score(0x12000034), 2);
+        assert!(Smallness.score(0x00000001) > Smallness.score(0x00000002));
+        assert_eq!(Smallness.score(0x00000000), u32::MAX);
+    }
+
+    #[test]
+    fn test_parse_objective() {
+        assert_eq!("leading-zero-bits".parse(), Ok(Objective::LeadingZeroBits));
+        assert_eq!("zero-bytes".parse(), Ok(Objective::ZeroBytes));
+        assert_eq!("smallness".parse(), Ok(Objective::Smallness));
+        assert!("largest".parse::<Objective>().is_err());
+        assert_eq!(Objective::ZeroBytes.score(0x00000001), 3);
+    }
+}
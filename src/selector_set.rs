@@ -0,0 +1,174 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    hash::{BuildHasherDefault, Hasher},
+    io,
+    path::Path,
+};
+
+use crate::pattern::parse_selector;
+
+/// Sets up to this size are kept sorted and binary searched
+const SORTED_THRESHOLD: usize = 64;
+/// Sets larger than this use a 2^32-bit bitmap (512 MiB) instead of a hash set
+const BITMAP_THRESHOLD: usize = 1 << 22;
+
+/// Selectors are mostly uniform already, so a multiply and fold is enough mixing
+#[derive(Default)]
+struct SelectorHasher(u64);
+
+impl Hasher for SelectorHasher {
+    fn finish(&self) -> u64 {
+        let hash = self.0.wrapping_mul(0x9E3779B97F4A7C15);
+        hash ^ (hash >> 32)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = self.0 << 8 | byte as u64;
+        }
+    }
+
+    fn write_u32(&mut self, selector: u32) {
+        self.0 = selector as u64;
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+enum Lookup {
+    Sorted(Vec<u32>),
+    Hashed(HashSet<u32, BuildHasherDefault<SelectorHasher>>),
+    Bitmap(Box<[u64]>),
+}
+
+/// A set of acceptable selectors, any one of which is a match.
+///
+/// The lookup structure is picked from the size of the set so that checking a candidate stays
+/// cheap whether the set holds a handful or millions of selectors.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SelectorSet {
+    lookup: Lookup,
+    len: usize,
+}
+
+impl SelectorSet {
+    pub fn new(selectors: impl IntoIterator<Item = u32>) -> Self {
+        let mut selectors: Vec<u32> = selectors.into_iter().collect();
+        selectors.sort_unstable();
+        selectors.dedup();
+        let len = selectors.len();
+
+        let lookup = if len <= SORTED_THRESHOLD {
+            Lookup::Sorted(selectors)
+        } else if len <= BITMAP_THRESHOLD {
+            Lookup::Hashed(selectors.into_iter().collect())
+        } else {
+            let mut bitmap = vec![0u64; 1 << 26].into_boxed_slice();
+            for selector in selectors {
+                bitmap[(selector >> 6) as usize] |= 1 << (selector & 63);
+            }
+            Lookup::Bitmap(bitmap)
+        };
+
+        Self { lookup, len }
+    }
+
+    /// Reads one selector per line, e.g. `0x12345678`.
+    ///
+    /// Blank lines and everything after a `#` are ignored.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    /// Same as [`SelectorSet::from_file`] on already loaded contents
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let mut selectors = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let selector = parse_selector(line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: invalid selector {:?}: {}", number + 1, line, e),
+                )
+            })?;
+            selectors.push(selector);
+        }
+        Ok(Self::new(selectors))
+    }
+
+    #[inline]
+    pub fn contains(&self, selector: u32) -> bool {
+        match &self.lookup {
+            Lookup::Sorted(selectors) => selectors.binary_search(&selector).is_ok(),
+            Lookup::Hashed(selectors) => selectors.contains(&selector),
+            Lookup::Bitmap(bitmap) => {
+                bitmap[(selector >> 6) as usize] & (1 << (selector & 63)) != 0
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl fmt::Debug for SelectorSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.lookup {
+            Lookup::Sorted(_) => "sorted",
+            Lookup::Hashed(_) => "hashed",
+            Lookup::Bitmap(_) => "bitmap",
+        };
+        f.debug_struct("SelectorSet").field("lookup", &kind).field("len", &self.len).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lookup, SelectorSet};
+
+    #[test]
+    fn test_contains() {
+        let small = SelectorSet::new([0xa9059cbb, 0x095ea7b3, 0xa9059cbb]);
+        assert!(matches!(small.lookup, Lookup::Sorted(_)));
+        assert_eq!(small.len(), 2);
+        assert!(small.contains(0xa9059cbb));
+        assert!(small.contains(0x095ea7b3));
+        assert!(!small.contains(0x23b872dd));
+
+        let large = SelectorSet::new((0..10_000u32).map(|i| i.wrapping_mul(0x9E3779B9)));
+        assert!(matches!(large.lookup, Lookup::Hashed(_)));
+        assert!(large.contains(1234u32.wrapping_mul(0x9E3779B9)));
+        assert!(!large.contains(1));
+    }
+
+    #[test]
+    #[ignore]
+    // Allocates the 512 MiB bitmap
+    fn test_bitmap() {
+        let huge = SelectorSet::new((0..(1u32 << 23)).map(|i| i * 3));
+        assert!(matches!(huge.lookup, Lookup::Bitmap(_)));
+        assert!(huge.contains(0));
+        assert!(huge.contains(3 * 12345));
+        assert!(!huge.contains(3 * 12345 + 1));
+    }
+
+    #[test]
+    fn test_parse() {
+        let set = SelectorSet::parse("# free slots\n0x00000001\n\n00000002 # no prefix\n").unwrap();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(1));
+        assert!(set.contains(2));
+
+        let error = SelectorSet::parse("0x00000001\nnope\n").unwrap_err();
+        assert!(error.to_string().starts_with("line 2:"));
+    }
+}
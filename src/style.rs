@@ -0,0 +1,163 @@
+/// Ways of joining the words of a function name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// `getBalance`
+    Camel,
+    /// `GetBalance`
+    Pascal,
+    /// `get_balance`
+    Snake,
+    /// `getbalance`
+    Flat,
+    /// `GET_BALANCE`
+    ScreamingSnake,
+}
+
+impl Style {
+    pub const ALL: [Style; 5] =
+        [Style::Camel, Style::Pascal, Style::Snake, Style::Flat, Style::ScreamingSnake];
+
+    /// Joins lower case `words` in this style
+    pub fn apply(&self, words: &[String]) -> String {
+        match self {
+            Style::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| if i == 0 { word.clone() } else { capitalize(word) })
+                .collect(),
+            Style::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+            Style::Snake => words.join("_"),
+            Style::Flat => words.concat(),
+            Style::ScreamingSnake => words.join("_").to_ascii_uppercase(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Splits a name into lower case words at underscores and case changes, `getHTTPStatus_v2`
+/// becomes `get`, `http`, `status`, `v2`
+pub fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_ascii_uppercase() && !current.is_empty() {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            // `aB` and `1B` start a word, so does the last capital of an acronym, `HTTPStatus`
+            if !previous.is_ascii_uppercase() || next_lower {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+/// Recognisable rewrites of `name`: every [`Style`], with and without each of the extra leading
+/// and trailing words, and with and without a trailing underscore.
+///
+/// `name` itself comes first and no variant is listed twice.
+pub fn style_variants(name: &str, prefixes: &[String], suffixes: &[String]) -> Vec<String> {
+    let words = split_words(name);
+    let optional = |extra: &[String]| {
+        std::iter::once(Vec::new())
+            .chain(extra.iter().map(|word| split_words(word)))
+            .collect::<Vec<_>>()
+    };
+
+    let mut variants = vec![name.to_string()];
+    for prefix in optional(prefixes) {
+        for suffix in optional(suffixes) {
+            let words = [prefix.as_slice(), &words, &suffix].concat();
+            for style in Style::ALL {
+                let styled = style.apply(&words);
+                for variant in [styled.clone(), styled + "_"] {
+                    if !variants.contains(&variant) {
+                        variants.push(variant);
+                    }
+                }
+            }
+        }
+    }
+
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Style, split_words, style_variants};
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("getBalance"), ["get", "balance"]);
+        assert_eq!(split_words("get_balance"), ["get", "balance"]);
+        assert_eq!(split_words("GetBalance"), ["get", "balance"]);
+        assert_eq!(split_words("getHTTPStatus_v2"), ["get", "http", "status", "v2"]);
+        assert_eq!(split_words("balanceOf2"), ["balance", "of2"]);
+        assert_eq!(split_words("__init__"), ["init"]);
+    }
+
+    #[test]
+    fn test_styles() {
+        let words = split_words("getBalance");
+        assert_eq!(Style::Camel.apply(&words), "getBalance");
+        assert_eq!(Style::Pascal.apply(&words), "GetBalance");
+        assert_eq!(Style::Snake.apply(&words), "get_balance");
+        assert_eq!(Style::Flat.apply(&words), "getbalance");
+        assert_eq!(Style::ScreamingSnake.apply(&words), "GET_BALANCE");
+    }
+
+    #[test]
+    fn test_variants() {
+        let variants = style_variants("getBalance", &[], &["Of".to_string()]);
+        assert_eq!(variants[0], "getBalance");
+        for expected in ["get_balance", "getbalance", "GetBalance", "getBalanceOf", "getBalance_"] {
+            assert!(variants.iter().any(|v| v == expected), "missing {}", expected);
+        }
+        assert!(variants.iter().any(|v| v == "get_balance_of_"));
+        // 5 styles, 2 underscores, with and without the affix
+        assert_eq!(variants.len(), 20);
+
+        let variants = style_variants("mint", &["try".to_string()], &[]);
+        assert_eq!(
+            variants,
+            [
+                "mint",
+                "mint_",
+                "Mint",
+                "Mint_",
+                "MINT",
+                "MINT_",
+                "tryMint",
+                "tryMint_",
+                "TryMint",
+                "TryMint_",
+                "try_mint",
+                "try_mint_",
+                "trymint",
+                "trymint_",
+                "TRY_MINT",
+                "TRY_MINT_"
+            ]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{pattern::Pattern, selector_set::SelectorSet};
+
+/// What the selector of a candidate has to satisfy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Masked compare against a (possibly anchored or floating) pattern
+    Pattern(Pattern),
+    /// Selector read as a big-endian `u32` lies in `low..=high`
+    Range { low: u32, high: u32 },
+    /// Selector is any one of a set of acceptable selectors
+    Set(SelectorSet),
+    /// Selector has at least this many zero bytes, in any position
+    ZeroBytes(u32),
+    /// Every one of the targets matches
+    All(Vec<Target>),
+    /// At least one of the targets matches
+    Any(Vec<Target>),
+    /// The target does not match
+    Not(Box<Target>),
+}
+
+/// Calldata gas for a zero byte
+pub const ZERO_BYTE_GAS: u64 = 4;
+/// Calldata gas for a non-zero byte
+pub const NON_ZERO_BYTE_GAS: u64 = 16;
+
+/// Number of zero bytes in the selector
+#[inline]
+pub fn zero_bytes(selector: u32) -> u32 {
+    selector.to_be_bytes().iter().filter(|&&byte| byte == 0).count() as u32
+}
+
+/// Calldata gas paid for the 4 selector bytes on every call
+pub fn calldata_gas(selector: u32) -> u64 {
+    let zeros = zero_bytes(selector) as u64;
+    zeros * ZERO_BYTE_GAS + (4 - zeros) * NON_ZERO_BYTE_GAS
+}
+
+impl Target {
+    /// Selector strictly below `bound`, `None` if nothing is
+    pub fn below(bound: u32) -> Option<Self> {
+        Some(Target::Range { low: 0, high: bound.checked_sub(1)? })
+    }
+
+    /// Selector strictly above `bound`, `None` if nothing is
+    pub fn above(bound: u32) -> Option<Self> {
+        Some(Target::Range { low: bound.checked_add(1)?, high: u32::MAX })
+    }
+
+    /// Selector in `low..=high`, `None` if the range is empty
+    pub fn between(low: u32, high: u32) -> Option<Self> {
+        (low <= high).then_some(Target::Range { low, high })
+    }
+
+    /// Selector with at least `count` zero bytes, `None` if `count` is more than 4
+    pub fn zero_bytes(count: u32) -> Option<Self> {
+        (count <= 4).then_some(Target::ZeroBytes(count))
+    }
+
+    #[inline]
+    pub fn matches(&self, selector: u32) -> bool {
+        match self {
+            Target::Pattern(pattern) => pattern.matches(selector),
+            Target::Range { low, high } => (*low..=*high).contains(&selector),
+            Target::Set(set) => set.contains(selector),
+            Target::ZeroBytes(count) => zero_bytes(selector) >= *count,
+            Target::All(targets) => targets.iter().all(|target| target.matches(selector)),
+            Target::Any(targets) => targets.iter().any(|target| target.matches(selector)),
+            Target::Not(target) => !target.matches(selector),
+        }
+    }
+
+    /// Probability that a random selector matches.
+    ///
+    /// Exact for a single predicate. Combinations treat their operands as independent, which is
+    /// only an estimate when they constrain the same bits, e.g. `prefix(00) & below(0x00100000)`.
+    pub fn probability(&self) -> f64 {
+        match self {
+            Target::Pattern(pattern) => pattern.probability(),
+            Target::Range { low, high } => (*high as f64 - *low as f64 + 1.0) / 2f64.powi(32),
+            Target::Set(set) => set.len() as f64 / 2f64.powi(32),
+            Target::ZeroBytes(count) => {
+                // Binomial over the 4 byte positions, each zero with probability 1/256
+                let zero = 1.0f64 / 256.0;
+                (*count..=4)
+                    .map(|k| {
+                        let positions = [1.0, 4.0, 6.0, 4.0, 1.0][k as usize];
+                        positions * zero.powi(k as i32) * (1.0 - zero).powi(4 - k as i32)
+                    })
+                    .sum()
+            }
+            Target::All(targets) => targets.iter().map(Target::probability).product(),
+            Target::Any(targets) => {
+                1.0 - targets.iter().map(|target| 1.0 - target.probability()).product::<f64>()
+            }
+            Target::Not(target) => 1.0 - target.probability(),
+        }
+    }
+
+    /// Expected number of hashes needed to find a match
+    pub fn difficulty(&self) -> f64 {
+        1.0 / self.probability()
+    }
+}
+
+impl From<Pattern> for Target {
+    fn from(pattern: Pattern) -> Self {
+        Target::Pattern(pattern)
+    }
+}
+
+impl From<SelectorSet> for Target {
+    fn from(set: SelectorSet) -> Self {
+        Target::Set(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Target, calldata_gas, zero_bytes};
+    use crate::{
+        pattern::{Anchor, Pattern},
+        selector_set::SelectorSet,
+    };
+
+    #[test]
+    fn test_range_matches() {
+        let below = Target::below(0x01000000).unwrap();
+        assert!(below.matches(0x00ffffff));
+        assert!(!below.matches(0x01000000));
+
+        let above = Target::above(0xfffffff0).unwrap();
+        assert!(above.matches(0xffffffff));
+        assert!(!above.matches(0xfffffff0));
+
+        let between = Target::between(0x10000000, 0x1fffffff).unwrap();
+        assert!(between.matches(0x10000000));
+        assert!(between.matches(0x1fffffff));
+        assert!(!between.matches(0x20000000));
+
+        // Empty ranges
+        assert!(Target::below(0).is_none());
+        assert!(Target::above(u32::MAX).is_none());
+        assert!(Target::between(2, 1).is_none());
+    }
+
+    #[test]
+    fn test_range_difficulty() {
+        assert_eq!(Target::below(0x01000000).unwrap().difficulty(), 256.0);
+        assert_eq!(Target::between(0x10000000, 0x1fffffff).unwrap().difficulty(), 16.0);
+        assert_eq!(Target::above(0).unwrap().probability(), 1.0 - 2f64.powi(-32));
+        assert_eq!(Target::between(0, u32::MAX).unwrap().difficulty(), 1.0);
+    }
+
+    #[test]
+    fn test_set_difficulty() {
+        let set: Target = SelectorSet::new((0..65536u32).map(|i| i << 16)).into();
+        assert_eq!(set.difficulty(), 65536.0);
+        assert!(set.matches(0x12340000));
+        assert!(!set.matches(0x12340001));
+    }
+
+    #[test]
+    fn test_zero_bytes() {
+        assert_eq!(zero_bytes(0x12000034), 2);
+        assert_eq!(zero_bytes(0x00001234), 2);
+        assert_eq!(zero_bytes(0x00000000), 4);
+        assert_eq!(zero_bytes(0x10203040), 0);
+
+        let target = Target::zero_bytes(2).unwrap();
+        assert!(target.matches(0x12000034));
+        assert!(target.matches(0x00001234));
+        assert!(target.matches(0x00000034));
+        assert!(!target.matches(0x12003456));
+        assert!(Target::zero_bytes(5).is_none());
+    }
+
+    #[test]
+    fn test_zero_bytes_difficulty() {
+        assert_eq!(Target::zero_bytes(0).unwrap().probability(), 1.0);
+        assert_eq!(Target::zero_bytes(4).unwrap().difficulty(), 2f64.powi(32));
+
+        // 6 ways to place two zero bytes, so roughly 6 times easier than a 2 byte prefix
+        let two = Target::zero_bytes(2).unwrap().difficulty();
+        assert!(two > 65536.0 / 6.0 && two < 65536.0 / 5.9);
+
+        // Exact count over all 2^16 values of a 2 byte selector half, squared up to 4 bytes
+        let mut per_half = [0u64; 3];
+        for half in 0..=u16::MAX {
+            per_half[half.to_be_bytes().iter().filter(|&&b| b == 0).count()] += 1;
+        }
+        let at_least_one = (0..3)
+            .flat_map(|a| (0..3).map(move |b| (a, b)))
+            .filter(|(a, b)| a + b >= 1)
+            .map(|(a, b)| per_half[a] * per_half[b])
+            .sum::<u64>() as f64;
+        let expected = at_least_one / 2f64.powi(32);
+        assert!((Target::zero_bytes(1).unwrap().probability() - expected).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_calldata_gas() {
+        assert_eq!(calldata_gas(0x12345678), 64);
+        assert_eq!(calldata_gas(0x12000034), 40);
+        assert_eq!(calldata_gas(0x00001234), 40);
+        assert_eq!(calldata_gas(0x00000000), 16);
+    }
+
+    #[test]
+    fn test_combinators() {
+        let prefix: Target = Pattern::parse("00").unwrap().into();
+        let suffix: Target = Pattern::parse_anchored("ff", Anchor::Suffix).unwrap().into();
+
+        let both = Target::All(vec![prefix.clone(), suffix.clone()]);
+        assert!(both.matches(0x001234ff));
+        assert!(!both.matches(0x001234fe));
+        assert_eq!(both.difficulty(), 65536.0);
+
+        let either = Target::Any(vec![prefix.clone(), suffix.clone()]);
+        assert!(either.matches(0x001234fe));
+        assert!(either.matches(0x101234ff));
+        assert!(!either.matches(0x101234fe));
+        assert_eq!(either.probability(), 1.0 - (255.0f64 / 256.0).powi(2));
+
+        let neither = Target::Not(Box::new(either));
+        assert!(neither.matches(0x101234fe));
+        assert_eq!(neither.probability(), (255.0f64 / 256.0).powi(2));
+    }
+}
@@ -0,0 +1,146 @@
+use std::{io, path::Path};
+
+/// Lists of words combined into readable names, one word from each list in order, e.g. verbs ×
+/// nouns × adjectives.
+///
+/// Combinations are numbered in mixed radix with the last list varying fastest, so index `n` is
+/// a fixed tuple of words and a range of indices can be split across workers like numerals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wordlists {
+    lists: Vec<Vec<Vec<u8>>>,
+    /// Number of combinations of the lists after each one
+    strides: Vec<u64>,
+    combinations: Option<u64>,
+}
+
+impl Wordlists {
+    /// `None` if there are no lists or one of them is empty
+    pub fn new(lists: Vec<Vec<String>>) -> Option<Self> {
+        if lists.is_empty() || lists.iter().any(Vec::is_empty) {
+            return None;
+        }
+        let lists: Vec<Vec<Vec<u8>>> = lists
+            .into_iter()
+            .map(|list| list.into_iter().map(String::into_bytes).collect())
+            .collect();
+
+        let mut strides = vec![1u64; lists.len()];
+        let mut combinations = Some(1u64);
+        for (i, list) in lists.iter().enumerate().rev() {
+            // Past u64::MAX the index never gets far enough for the stride to matter
+            strides[i] = combinations.unwrap_or(u64::MAX);
+            combinations = combinations.and_then(|c| c.checked_mul(list.len() as u64));
+        }
+
+        Some(Self { lists, strides, combinations })
+    }
+
+    /// Reads one word per line, ignoring blank lines and everything after a `#`
+    pub fn parse_list(contents: &str) -> Vec<String> {
+        contents
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// One list per file, see [`Wordlists::parse_list`]
+    pub fn from_files(paths: &[impl AsRef<Path>]) -> io::Result<Option<Self>> {
+        let lists = paths
+            .iter()
+            .map(|path| std::fs::read_to_string(path).map(|contents| Self::parse_list(&contents)))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::new(lists))
+    }
+
+    pub fn len(&self) -> usize {
+        self.lists.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lists.is_empty()
+    }
+
+    /// Number of word tuples, `None` if there are more than a `u64` index can reach
+    pub fn combinations(&self) -> Option<u64> {
+        self.combinations
+    }
+
+    /// Words of list `list`
+    pub fn list(&self, list: usize) -> impl Iterator<Item = &str> {
+        // Words come from `String`s
+        self.lists[list].iter().map(|word| std::str::from_utf8(word).unwrap())
+    }
+
+    /// Word picked from list `list` for `index`
+    #[inline]
+    pub fn word(&self, index: u64, list: usize) -> &[u8] {
+        let words = &self.lists[list];
+        &words[((index / self.strides[list]) % words.len() as u64) as usize]
+    }
+
+    /// Word tuple for `index`
+    pub fn words(&self, index: u64) -> Vec<&str> {
+        // Words come from `String`s
+        (0..self.lists.len())
+            .map(|list| std::str::from_utf8(self.word(index, list)).unwrap())
+            .collect()
+    }
+
+    /// Appends the word of `list` for `index`, capitalized unless it starts the buffer so that
+    /// words are camelCased onto the name
+    #[inline]
+    pub fn write_word(&self, index: u64, list: usize, buffer: &mut Vec<u8>) {
+        let word = self.word(index, list);
+        if let Some((&first, rest)) = word.split_first() {
+            buffer.push(if buffer.is_empty() { first } else { first.to_ascii_uppercase() });
+            buffer.extend_from_slice(rest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Wordlists;
+
+    fn lists() -> Wordlists {
+        Wordlists::new(vec![
+            vec!["get".into(), "set".into()],
+            vec!["owner".into(), "fee".into(), "limit".into()],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mixed_radix() {
+        let lists = lists();
+        assert_eq!(lists.combinations(), Some(6));
+        assert_eq!(lists.words(0), ["get", "owner"]);
+        assert_eq!(lists.words(1), ["get", "fee"]);
+        assert_eq!(lists.words(3), ["set", "owner"]);
+        assert_eq!(lists.words(5), ["set", "limit"]);
+
+        let mut buffer = b"pool".to_vec();
+        lists.write_word(4, 0, &mut buffer);
+        lists.write_word(4, 1, &mut buffer);
+        assert_eq!(buffer, b"poolSetFee");
+
+        let mut buffer = Vec::new();
+        lists.write_word(4, 0, &mut buffer);
+        assert_eq!(buffer, b"set");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Wordlists::parse_list("# verbs\nget\n\n set # common\n"), ["get", "set"]);
+        assert!(Wordlists::new(vec![]).is_none());
+        assert!(Wordlists::new(vec![vec!["get".into()], vec![]]).is_none());
+
+        // 2^64 combinations do not fit an index
+        let huge =
+            Wordlists::new(vec![(0..65536).map(|i| format!("w{}", i)).collect(); 4]).unwrap();
+        assert_eq!(huge.combinations(), None);
+        assert_eq!(huge.words(65536), ["w0", "w0", "w1", "w0"]);
+    }
+}
@@ -1,23 +1,158 @@
-use vanity_4b::{calculate_keccak_256, cli::Opts, generate_vanity_function_name};
+use gumdrop::Options;
+use vanity_4b::{
+    alphabet::Alphabet,
+    backend::Backend,
+    calculate_keccak_256,
+    candidate::{CandidateGenerator, Candidates, Suffix},
+    cli::Opts,
+    deny_list::DenyList,
+    generate_vanity_function_name,
+    pattern::{Anchor, Pattern},
+    score::Objective,
+    selector_set::SelectorSet,
+    style::style_variants,
+    wordlist::Wordlists,
+};
 
 #[test]
 fn test_cli_argument_parsing() {
-    use gumdrop::Options;
-
     // Test valid arguments
     let args = vec!["-x", "0x1234", "-f", "test", "-p", "uint256", "-t", "4"];
 
     let opts = Opts::parse_args_default(&args).unwrap();
-    assert_eq!(opts.pattern, "0x1234");
+    assert_eq!(opts.pattern, Some("0x1234".to_string()));
     assert_eq!(opts.fn_name, "test");
     assert_eq!(opts.fn_parameters, Some("uint256".to_string()));
     assert_eq!(opts.num_threads, Some(4));
 
     // Test missing required arguments
-    let incomplete_args = vec!["-f", "test"];
+    let incomplete_args = vec!["-x", "0x1234"];
     assert!(Opts::parse_args_default(&incomplete_args).is_err());
 }
 
+#[test]
+fn test_cli_mask_value() {
+    // Mask and value are parsed as hex selectors
+    let args = vec!["--mask", "0xffe00000", "--value", "0x00000000", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.pattern, None);
+    assert_eq!(opts.mask, Some(0xffe00000));
+    assert_eq!(opts.value, Some(0));
+
+    let invalid_args = vec!["--mask", "0xzz", "-f", "test"];
+    assert!(Opts::parse_args_default(&invalid_args).is_err());
+}
+
+#[test]
+fn test_cli_anchor() {
+    let args = vec!["-x", "beef", "-a", "suffix", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.anchor, Some(Anchor::Suffix));
+    let args = vec!["-x", "beef", "--anchor", "2", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.anchor, Some(Anchor::Offset(2)));
+    let invalid_args = vec!["-x", "beef", "--anchor", "middle", "-f", "test"];
+    assert!(Opts::parse_args_default(&invalid_args).is_err());
+}
+
+#[test]
+fn test_cli_ranges() {
+    let args = vec!["--below", "0x01000000", "--between", "0x10000000", "0x1fffffff", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.below, Some(0x01000000));
+    assert_eq!(opts.between, Some((0x10000000, 0x1fffffff)));
+    let args = vec!["--below", "0X01000000", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.below, Some(0x01000000));
+}
+
+#[test]
+fn test_cli_optimize() {
+    let args = vec!["--optimize", "zero-bytes", "--time-budget", "60", "--top", "5", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.optimize, Some(Objective::ZeroBytes));
+    assert_eq!(opts.time_budget, Some(60));
+    assert_eq!(opts.top, Some(5));
+}
+
+#[test]
+fn test_cli_expression() {
+    let args = vec!["-e", "prefix(00) & !zeros(3)", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.expr, Some("prefix(00) & !zeros(3)".to_string()));
+}
+
+#[test]
+fn test_cli_alphabet() {
+    let args = vec!["-x", "00", "--alphabet", "identifier", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.alphabet, Some(Alphabet::Identifier));
+    let args = vec!["-x", "00", "--alphabet", "custom:abc", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.alphabet, Some(Alphabet::Custom(b"abc".to_vec())));
+    let invalid_args = vec!["-x", "00", "--alphabet", "custom:a-b", "-f", "test"];
+    assert!(Opts::parse_args_default(&invalid_args).is_err());
+}
+
+#[test]
+fn test_cli_width() {
+    let args = vec!["-x", "00", "--width", "6", "-f", "transfer_"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.width, Some(6));
+}
+
+#[test]
+fn test_cli_name_length() {
+    let args = vec!["-x", "00", "--max-name-len", "8", "--shortest", "-f", "mint"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.max_name_len, Some(8));
+    assert!(opts.shortest);
+}
+
+#[test]
+fn test_cli_joint() {
+    let args = vec!["-x", "00;11", "--joint", "-f", "swap", "-p", "uint256;uint256,address"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert!(opts.joint);
+    assert_eq!(opts.pattern, Some("00;11".to_string()));
+}
+
+#[test]
+fn test_cli_backend() {
+    // Keccak-256 backend, any compiled in
+    let backend = Backend::default().to_string();
+    let args = vec!["-x", "00", "--backend", &backend, "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.backend, Some(Backend::default()));
+    let invalid_args = vec!["-x", "00", "--backend", "openssl", "-f", "test"];
+    assert!(Opts::parse_args_default(&invalid_args).is_err());
+}
+
+#[test]
+fn test_backend_workflow() {
+    // Every backend hashes the search itself and finds the same first match
+    let candidates = Candidates::decimal("transfer", "address,uint256").unwrap();
+    let target = Pattern::parse("00").unwrap().into();
+    let solutions: Vec<Option<u64>> = Backend::ALL
+        .iter()
+        .map(|&backend| {
+            generate_vanity_function_name(
+                &target,
+                &DenyList::default(),
+                &candidates,
+                0,
+                Some(300),
+                backend,
+            )
+        })
+        .collect();
+    // A single chunk, so the match is the first one
+    let expected =
+        (0..300).find(|&n| calculate_keccak_256(candidates.render(n).as_bytes())[0] == 0);
+    assert!(expected.is_some());
+    assert!(solutions.iter().all(|&solution| solution == expected), "{:?}", solutions);
+}
+
 #[test]
 fn test_complete_workflow() {
     // This test simulates the complete command but with a small range
@@ -27,11 +162,12 @@ fn test_complete_workflow() {
 
     // Run the generator with a small range
     let solution = generate_vanity_function_name(
-        pattern[2..].as_bytes(),
-        fn_name.as_bytes(),
-        fn_params.as_bytes(),
+        &Pattern::parse(pattern).unwrap().into(),
+        &DenyList::default(),
+        &Candidates::decimal(fn_name, fn_params).unwrap(),
         0,
         Some(100000),
+        Backend::default(),
     );
 
     assert!(solution.is_some());
@@ -42,3 +178,200 @@ fn test_complete_workflow() {
 
     assert_eq!(format!("0x{:02x}{:02x}", hash[0], hash[1]), pattern);
 }
+
+#[test]
+fn test_cli_targets_file() {
+    let args = vec!["--targets-file", "free_selectors.txt", "-f", "test"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.targets_file, Some("free_selectors.txt".to_string()));
+}
+
+#[test]
+fn test_targets_file_workflow() {
+    let path = std::env::temp_dir().join(format!("vanity-4b-targets-{}.txt", std::process::id()));
+    // Three acceptable selectors for "transfer<N>(address,uint256)"
+    let selectors: Vec<String> = [12345u64, 23456, 34567]
+        .iter()
+        .map(|n| {
+            let hash = calculate_keccak_256(format!("transfer{}(address,uint256)", n).as_bytes());
+            format!("0x{:02x}{:02x}{:02x}{:02x}", hash[0], hash[1], hash[2], hash[3])
+        })
+        .collect();
+    std::fs::write(&path, format!("# acceptable\n{}\n", selectors.join("\n"))).unwrap();
+
+    let set = SelectorSet::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(set.len(), 3);
+
+    let solution = generate_vanity_function_name(
+        &set.into(),
+        &DenyList::default(),
+        &Candidates::decimal("transfer", "address,uint256").unwrap(),
+        20000,
+        Some(40000),
+        Backend::default(),
+    );
+    assert_eq!(solution, Some(23456));
+}
+
+#[test]
+fn test_cli_deny_list() {
+    let args = vec!["-x", "00", "--deny", "0x00000000,0xffffffff", "--deny-file", "clash.txt"];
+    let opts = Opts::parse_args_default(&[args, vec!["-f", "test"]].concat()).unwrap();
+    assert_eq!(opts.deny, Some("0x00000000,0xffffffff".to_string()));
+    assert_eq!(opts.deny_file, Some("clash.txt".to_string()));
+}
+
+#[test]
+fn test_deny_list_workflow() {
+    // Any selector matches, so the search returns the first candidate that is not denied
+    // Candidate 0 has no numeric suffix
+    let hash = |n: u64| match n {
+        0 => calculate_keccak_256(b"deposit(uint256)"),
+        n => calculate_keccak_256(format!("deposit{}(uint256)", n).as_bytes()),
+    };
+    let denied: Vec<String> = (0..3u64)
+        .map(|n| {
+            format!("0x{:02x}{:02x}{:02x}{:02x}", hash(n)[0], hash(n)[1], hash(n)[2], hash(n)[3])
+        })
+        .collect();
+    let deny_list = DenyList::parse(&denied.join(",")).unwrap();
+
+    let solution = generate_vanity_function_name(
+        &Pattern::default().into(),
+        &deny_list,
+        &Candidates::decimal("deposit", "uint256").unwrap(),
+        0,
+        Some(4),
+        Backend::default(),
+    );
+    assert_eq!(solution, Some(3));
+
+    let solution = generate_vanity_function_name(
+        &Pattern::default().into(),
+        &deny_list,
+        &Candidates::decimal("deposit", "uint256").unwrap(),
+        0,
+        Some(3),
+        Backend::default(),
+    );
+    assert_eq!(solution, None);
+}
+
+#[test]
+fn test_cli_wordlists() {
+    // Wordlists, one per option
+    let args = vec!["-x", "00", "--wordlist", "verbs.txt", "--wordlist", "nouns.txt", "-f", "pool"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert_eq!(opts.wordlist, vec!["verbs.txt".to_string(), "nouns.txt".to_string()]);
+}
+
+#[test]
+fn test_wordlist_workflow() {
+    let dir = std::env::temp_dir();
+    let verbs = dir.join(format!("vanity-4b-verbs-{}.txt", std::process::id()));
+    let nouns = dir.join(format!("vanity-4b-nouns-{}.txt", std::process::id()));
+    std::fs::write(&verbs, "# verbs\nget\nset\nadd\nremove\n").unwrap();
+    std::fs::write(&nouns, "owner\nfee\nlimit\nrate\ncap\n").unwrap();
+
+    let wordlists = Wordlists::from_files(&[&verbs, &nouns]).unwrap().unwrap();
+    std::fs::remove_file(&verbs).unwrap();
+    std::fs::remove_file(&nouns).unwrap();
+    let candidates = Candidates::from_wordlists("pool", "uint256", wordlists).unwrap();
+    assert_eq!(candidates.space(), Some(20));
+
+    // Only the selector of poolRemoveRate is acceptable
+    let hash = calculate_keccak_256(b"poolRemoveRate(uint256)");
+    let target = SelectorSet::new([u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])]);
+
+    let solution = generate_vanity_function_name(
+        &target.into(),
+        &DenyList::default(),
+        &candidates,
+        0,
+        None,
+        Backend::default(),
+    );
+    assert_eq!(solution, Some(3 * 5 + 3));
+    assert_eq!(candidates.render(18), "poolRemoveRate(uint256)");
+    assert_eq!(candidates.words(18), Some(vec!["remove", "rate"]));
+}
+
+#[test]
+fn test_cli_style_variants() {
+    let args = vec!["-x", "00", "--styles", "--suffix-words", "of", "--no-suffix", "-f", "get"];
+    let opts = Opts::parse_args_default(&args).unwrap();
+    assert!(opts.styles && opts.no_suffix);
+    assert!(!opts.shortest);
+    assert_eq!(opts.suffix_words, Some("of".to_string()));
+}
+
+#[test]
+fn test_style_variants_workflow() {
+    let names = style_variants("getBalance", &[], &["of".to_string()]);
+    let bases = names.iter().map(|name| (name.as_str(), "address"));
+    let candidates = Candidates::from_bases(bases, Suffix::None).unwrap();
+
+    // Only the snake_case variant with the extra word is acceptable
+    let hash = calculate_keccak_256(b"get_balance_of(address)");
+    let target = SelectorSet::new([u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])]);
+
+    let solution = generate_vanity_function_name(
+        &target.into(),
+        &DenyList::default(),
+        &candidates,
+        0,
+        None,
+        Backend::default(),
+    );
+    assert_eq!(candidates.render(solution.unwrap()), "get_balance_of(address)");
+}
+
+/// Strategy outside the library, `f_0x` + the index in fixed width hex
+struct HexIndex;
+
+impl CandidateGenerator for HexIndex {
+    fn space(&self) -> Option<u64> {
+        Some(1 << 16)
+    }
+
+    fn write(&self, index: u64, buffer: &mut Vec<u8>) {
+        buffer.clear();
+        buffer.extend_from_slice(format!("f_0x{:04x}()", index).as_bytes());
+    }
+}
+
+#[test]
+fn test_custom_generator_workflow() {
+    let target = Pattern::parse("00").unwrap().into();
+    let solution = generate_vanity_function_name(
+        &target,
+        &DenyList::default(),
+        &HexIndex,
+        0,
+        None,
+        Backend::default(),
+    )
+    .unwrap();
+    let name = HexIndex.render(solution);
+    assert!(name.starts_with("f_0x"));
+    assert_eq!(calculate_keccak_256(name.as_bytes())[0], 0);
+
+    // Every candidate was tried, none matched
+    let target = SelectorSet::new([0xdeadbeef]).into();
+    assert_eq!(
+        generate_vanity_function_name(
+            &target,
+            &DenyList::default(),
+            &HexIndex,
+            0,
+            None,
+            Backend::default()
+        ),
+        None
+    );
+
+    // Usable behind a trait object as well
+    let generator: &dyn CandidateGenerator = &HexIndex;
+    assert_eq!(generator.range_end(Some(1 << 20)), 1 << 16);
+}
@@ -1,7 +1,16 @@
 use std::time::Instant;
 
 use rayon::ThreadPoolBuilder;
-use vanity_4b::{calculate_keccak_256, generate_vanity_function_name};
+use vanity_4b::{
+    backend::Backend,
+    calculate_keccak_256,
+    candidate::{CandidateGenerator, Candidates},
+    deny_list::DenyList,
+    generate_vanity_function_name, hash_selector,
+    keccak::{Kernel, SelectorHasher},
+    pattern::Pattern,
+    selector_set::SelectorSet,
+};
 
 #[test]
 #[ignore]
@@ -97,8 +106,22 @@ fn run_with_threads(
     let start = Instant::now();
 
     // Use the pool to run the task
+    let pattern = std::str::from_utf8(pattern).unwrap();
+    let target = Pattern::parse(pattern).expect("Invalid pattern").into();
+    let candidates = Candidates::decimal(
+        std::str::from_utf8(fn_name).unwrap(),
+        std::str::from_utf8(fn_params).unwrap(),
+    )
+    .unwrap();
     let solution = pool.install(|| {
-        generate_vanity_function_name(pattern, fn_name, fn_params, range_start, Some(range_end))
+        generate_vanity_function_name(
+            &target,
+            &DenyList::default(),
+            &candidates,
+            range_start,
+            Some(range_end),
+            Backend::default(),
+        )
     });
 
     let elapsed = start.elapsed();
@@ -108,15 +131,147 @@ fn run_with_threads(
 
     // Log if a solution was found (useful for debugging)
     if let Some(idx) = solution {
-        full_name = format!(
-            "{}{}({})",
-            std::str::from_utf8(fn_name).unwrap(),
-            idx,
-            std::str::from_utf8(fn_params).unwrap()
-        );
+        full_name = candidates.render(idx);
         let hash = calculate_keccak_256(full_name.as_bytes());
         signature = format!("0x{:02x}{:02x}{:02x}{:02x}", hash[0], hash[1], hash[2], hash[3]);
     }
 
     (elapsed, full_name, signature)
 }
+
+#[test]
+#[ignore]
+// Can be run with: -- --include-ignored
+// Show output: -- --nocapture
+fn benchmark_odometer_search() {
+    // Single core, rewriting every candidate against the search counting digits in place
+    let iterations = 5_000_000;
+    let candidates = Candidates::decimal("doSomething", "address").unwrap();
+    let range_start = 525_000_000;
+
+    let start = Instant::now();
+    let mut buffer = Vec::new();
+    for i in range_start..range_start + iterations {
+        candidates.write(i, &mut buffer);
+        let _hash = calculate_keccak_256(&buffer);
+    }
+    let rewrite = iterations as f64 / start.elapsed().as_secs_f64();
+
+    // No selector matches, so the whole range is searched
+    let target = SelectorSet::new([]).into();
+    let pool =
+        ThreadPoolBuilder::new().num_threads(1).build().expect("Failed to build thread pool");
+    let start = Instant::now();
+    let solution = pool.install(|| {
+        generate_vanity_function_name(
+            &target,
+            &DenyList::default(),
+            &candidates,
+            range_start,
+            Some(range_start + iterations),
+            Backend::default(),
+        )
+    });
+    let odometer = iterations as f64 / start.elapsed().as_secs_f64();
+    assert_eq!(solution, None);
+
+    println!("Rewriting every candidate: {:.2} MH/s", rewrite / 1_000_000.0);
+    println!("Odometer search: {:.2} MH/s", odometer / 1_000_000.0);
+    assert!(odometer > rewrite, "Counting in place should beat rewriting every candidate");
+}
+
+#[test]
+#[ignore]
+// Can be run with: -- --include-ignored
+// Show output: -- --nocapture
+fn benchmark_single_block_kernel() {
+    // Single core, the general digest against the single block selector kernel
+    let iterations = 5_000_000;
+    let mut input = *b"doSomething000000(address)";
+
+    let start = Instant::now();
+    let mut digest = 0u32;
+    for i in 0..iterations {
+        input[11..17].copy_from_slice(format!("{:06}", i % 1_000_000).as_bytes());
+        digest ^= hash_selector(&calculate_keccak_256(&input));
+    }
+    let digest_rate = iterations as f64 / start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let mut hasher = SelectorHasher::new(11);
+    let mut kernel = 0u32;
+    for i in 0..iterations {
+        input[11..17].copy_from_slice(format!("{:06}", i % 1_000_000).as_bytes());
+        kernel ^= hasher.selector(&input);
+    }
+    let kernel_rate = iterations as f64 / start.elapsed().as_secs_f64();
+    assert_eq!(digest, kernel);
+
+    println!("Keccak-256 digest: {:.2} MH/s", digest_rate / 1_000_000.0);
+    println!("Single block kernel: {:.2} MH/s", kernel_rate / 1_000_000.0);
+}
+
+#[test]
+#[ignore]
+// Can be run with: -- --include-ignored
+// Show output: -- --nocapture
+fn benchmark_simd_kernels() {
+    // Single core, every kernel the CPU supports on the same inputs
+    let iterations = 5_000_000;
+    let mut reference = None;
+    for kernel in [Kernel::Scalar, Kernel::Avx2, Kernel::Avx512] {
+        if !kernel.is_supported() {
+            println!("{}: not supported", kernel);
+            continue;
+        }
+        let mut hasher = SelectorHasher::with_kernel(11, kernel);
+        let lanes = hasher.lanes();
+        let mut inputs = vec![*b"doSomething000000(address)"; lanes];
+
+        let start = Instant::now();
+        let mut digest = 0u32;
+        for i in (0..iterations).step_by(lanes) {
+            for (lane, input) in inputs.iter_mut().enumerate() {
+                input[11..17].copy_from_slice(format!("{:06}", (i + lane) % 1_000_000).as_bytes());
+            }
+            digest = hasher.selectors(&inputs)[..lanes].iter().fold(digest, |a, s| a ^ s);
+        }
+        let rate = iterations as f64 / start.elapsed().as_secs_f64();
+        assert_eq!(*reference.get_or_insert(digest), digest);
+
+        println!("{}: {:.2} MH/s", kernel, rate / 1_000_000.0);
+    }
+}
+
+#[test]
+#[ignore]
+// Can be run with: -- --include-ignored
+// Show output: -- --nocapture
+fn benchmark_midstate() {
+    // Single core, a name of more than a block, re-absorbed per candidate against absorbed once
+    let iterations = 2_000_000;
+    let prefix = "executeBatchedSwapWithPermitAndCallback".repeat(4);
+    let mut input = format!("{}000000(address,uint256)", prefix).into_bytes();
+    let digits = prefix.len()..prefix.len() + 6;
+
+    let start = Instant::now();
+    let mut digest = 0u32;
+    for i in 0..iterations {
+        input[digits.clone()].copy_from_slice(format!("{:06}", i % 1_000_000).as_bytes());
+        digest ^= hash_selector(&calculate_keccak_256(&input));
+    }
+    let digest_rate = iterations as f64 / start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let mut hasher = SelectorHasher::new(prefix.len());
+    let mut midstate = 0u32;
+    for i in 0..iterations {
+        input[digits.clone()].copy_from_slice(format!("{:06}", i % 1_000_000).as_bytes());
+        midstate ^= hasher.selector(&input);
+    }
+    let midstate_rate = iterations as f64 / start.elapsed().as_secs_f64();
+    assert_eq!(digest, midstate);
+
+    println!("Keccak-256 digest: {:.2} MH/s", digest_rate / 1_000_000.0);
+    println!("Midstate of {} bytes: {:.2} MH/s", hasher.absorbed(), midstate_rate / 1_000_000.0);
+}